@@ -0,0 +1,193 @@
+use core::spec::{FromValue, Value};
+use core::Consts;
+use core::Pitch;
+use error::*;
+use std::str::FromStr;
+
+/// A set of pitches played together
+///
+/// Parsed from a root [`Pitch`](enum.Pitch.html) followed by a quality, e.g.
+/// `a4 maj`, `c#5 min7` or `Bb3 sus4`. The quality can be followed by:
+/// - `inversion <n>` to move the lowest note up an octave `n` times
+/// - `octave <n>` to voice the chord so its lowest note is in octave `n`
+///
+/// E.g. `c4 maj7 inversion 1 octave 3`.
+#[derive(Clone, Debug)]
+pub struct Chord {
+    /// Pitches from lowest to highest, before being moved to `octave`
+    pitches: Vec<Pitch>,
+    octave: Option<i32>,
+}
+
+impl Chord {
+    /// Create a chord from a root and the intervals of each note in semitones
+    pub fn new(root: &Pitch, intervals: &[i32]) -> Self {
+        Chord {
+            pitches: intervals
+                .iter()
+                .map(|interval| root.transpose(f64::from(*interval)))
+                .collect(),
+            octave: None,
+        }
+    }
+
+    /// Move the lowest note up an octave, `inversion` times
+    pub fn invert(mut self, inversion: usize) -> Self {
+        for _ in 0..inversion {
+            let lowest = self.pitches.remove(0);
+            self.pitches.push(lowest.transpose(12.0));
+        }
+        self
+    }
+
+    /// Voice the chord so that its lowest note is in `octave`
+    pub fn in_octave(mut self, octave: i32) -> Self {
+        self.octave = Some(octave);
+        self
+    }
+
+    /// Get the pitches in the chord, from lowest to highest
+    pub fn pitches(&self, consts: &Consts) -> Vec<Pitch> {
+        let semitones = match (self.octave, self.pitches.first()) {
+            (Some(octave), Some(lowest)) => {
+                let lowest_octave = (lowest.to_midi(consts).round() / 12.0).floor() - 1.0;
+                12.0 * (f64::from(octave) - lowest_octave)
+            }
+            _ => 0.0,
+        };
+        self.pitches
+            .iter()
+            .map(|p| p.transpose(semitones))
+            .collect()
+    }
+
+    /// Get the frequencies in the chord, from lowest to highest
+    pub fn frequencies(&self, consts: &Consts) -> Vec<f64> {
+        self.pitches(consts)
+            .iter()
+            .map(|p| p.to_hz(consts))
+            .collect()
+    }
+
+    fn quality_intervals(quality: &str) -> Option<&'static [i32]> {
+        match quality {
+            "maj" => Some(&[0, 4, 7]),
+            "min" | "m" => Some(&[0, 3, 7]),
+            "dim" => Some(&[0, 3, 6]),
+            "aug" => Some(&[0, 4, 8]),
+            "sus2" => Some(&[0, 2, 7]),
+            "sus4" | "sus" => Some(&[0, 5, 7]),
+            "6" => Some(&[0, 4, 7, 9]),
+            "min6" | "m6" => Some(&[0, 3, 7, 9]),
+            "7" => Some(&[0, 4, 7, 10]),
+            "maj7" => Some(&[0, 4, 7, 11]),
+            "min7" | "m7" => Some(&[0, 3, 7, 10]),
+            "dim7" => Some(&[0, 3, 6, 9]),
+            "m7b5" => Some(&[0, 3, 6, 10]),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Chord {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Chord> {
+        let tokens: Vec<&str> = string.split_whitespace().collect();
+        let quality_index = (1..tokens.len())
+            .find(|i| Chord::quality_intervals(tokens[*i]).is_some())
+            .ok_or_else(|| {
+                ErrorKind::SpecError(format!("Missing chord quality in chord: {}", string))
+            })?;
+        let root: Pitch = tokens[..quality_index]
+            .join(" ")
+            .parse()
+            .chain_err(|| format!("Failed to parse root of chord: {}", string))?;
+        let intervals = Chord::quality_intervals(tokens[quality_index]).unwrap();
+
+        let mut inversion = 0;
+        let mut octave = None;
+        for modifier in tokens[quality_index + 1..].chunks(2) {
+            match modifier {
+                ["inversion", number] => {
+                    inversion = number
+                        .parse()
+                        .chain_err(|| "Failed to parse chord inversion")?
+                }
+                ["octave", number] => {
+                    octave = Some(
+                        number
+                            .parse()
+                            .chain_err(|| "Failed to parse chord octave")?,
+                    )
+                }
+                _ => bail!(ErrorKind::SpecError(format!(
+                    "Unrecognized chord modifier: {}",
+                    modifier.join(" ")
+                ))),
+            }
+        }
+
+        let chord = Chord::new(&root, intervals).invert(inversion);
+        match octave {
+            Some(octave) => Ok(chord.in_octave(octave)),
+            None => Ok(chord),
+        }
+    }
+}
+
+impl FromValue for Chord {
+    fn name() -> String {
+        "chord".into()
+    }
+    fn from_value(value: Value, consts: &Consts) -> Result<Chord> {
+        let string: String = value.into_type(consts)?;
+        string.parse()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn midis(string: &str) -> Vec<f64> {
+        let consts = Consts::default().unwrap();
+        let chord: Chord = string.parse().unwrap();
+        chord
+            .pitches(&consts)
+            .iter()
+            .map(|p| p.to_midi(&consts).round())
+            .collect()
+    }
+
+    #[test]
+    fn test_qualities() {
+        assert_eq!(midis("a4 maj"), vec![69.0, 73.0, 76.0]);
+        assert_eq!(midis("c4 min"), vec![60.0, 63.0, 67.0]);
+        assert_eq!(midis("Bb3 7"), vec![58.0, 62.0, 65.0, 68.0]);
+        assert_eq!(midis("60 dim7"), vec![60.0, 63.0, 66.0, 69.0]);
+        assert_eq!(midis("440 hz sus4"), vec![69.0, 74.0, 76.0]);
+        assert!("a4".parse::<Chord>().is_err());
+        assert!("a4 major".parse::<Chord>().is_err());
+        assert!("h4 maj".parse::<Chord>().is_err());
+    }
+
+    #[test]
+    fn test_voicing() {
+        assert_eq!(midis("c4 maj inversion 1"), vec![64.0, 67.0, 72.0]);
+        assert_eq!(midis("c4 maj inversion 2"), vec![67.0, 72.0, 76.0]);
+        assert_eq!(midis("c4 maj octave 2"), vec![36.0, 40.0, 43.0]);
+        assert_eq!(midis("c4 maj inversion 1 octave 5"), vec![76.0, 79.0, 84.0]);
+        assert!("c4 maj inversion".parse::<Chord>().is_err());
+        assert!("c4 maj voicing 2".parse::<Chord>().is_err());
+    }
+
+    #[test]
+    fn test_frequencies() {
+        let consts = Consts::default().unwrap();
+        let chord: Chord = "a4 maj".parse().unwrap();
+        let frequencies = chord.frequencies(&consts);
+        assert_eq!(frequencies[0], 440.0);
+        assert!((frequencies[2] - 659.25).abs() < 0.01);
+    }
+}
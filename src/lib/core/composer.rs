@@ -1,17 +1,27 @@
 //! Create compositions
 
 use core::get_reloading_player;
+use core::spec::expand_spec;
 use core::spec::read;
 use core::spec::read::ReadType;
+use core::spec::Value;
 use core::Consts;
 use core::Output;
+use core::Playable;
+use core::Player;
 use core::State;
+use core::Time;
+use core::NUM_CHANNELS;
 use error::*;
 use gui;
+use outputs::WavFile;
 
 use std::path::Path;
 use std::sync::Arc;
 
+/// How many `Playable`s are created at a time
+const BLOCK_SIZE: usize = 256;
+
 /// Start a composition from a file
 pub fn compose_from_file(
     composition_path: String,
@@ -23,12 +33,66 @@ pub fn compose_from_file(
     let reloading_player = get_reloading_player(composition_path, read_type, consts.clone())?;
     gui::start(reloading_player.clone())?;
     let mut state = State::initial(consts);
+    let mut block = [Playable::zero(); BLOCK_SIZE];
     loop {
-        let played = reloading_player.lock().unwrap().play(&state);
-        for output in outputs.iter_mut() {
-            output.write(played);
+        reloading_player
+            .lock()
+            .unwrap()
+            .play_block(&state, &mut block);
+        write_block(&block, &mut outputs);
+        state.increment_by(BLOCK_SIZE);
+    }
+}
+
+/// Render a composition from a file into a .wav file, as fast as possible
+///
+/// Only the constants are taken from the configuration file, the outputs in it
+/// are ignored.
+pub fn render_from_file(
+    composition_path: String,
+    read_type: ReadType,
+    config_path: String,
+    duration: Time,
+    output_path: String,
+) -> Result<()> {
+    let consts = Arc::new(get_consts_from_config(config_path)?);
+    let spec = read::path_to_spec(Path::new(&composition_path), read_type)?;
+    let spec = expand_spec(spec, &consts)?;
+    let mut player: Box<dyn Player> = Value::Spec(spec).into_type(&consts)?;
+    let mut outputs: Vec<Box<dyn Output>> = vec![Box::new(WavFile::new(
+        output_path,
+        16,
+        NUM_CHANNELS,
+        false,
+        &consts,
+    )?)];
+    render(&mut *player, &mut outputs, &duration, consts);
+    Ok(())
+}
+
+/// Play a player for a fixed duration, without waiting for real time
+pub fn render(
+    player: &mut dyn Player,
+    outputs: &mut [Box<dyn Output>],
+    duration: &Time,
+    consts: Arc<Consts>,
+) {
+    let num_ticks = duration.to_ticks(&consts);
+    let mut state = State::initial(consts);
+    let mut block = [Playable::zero(); BLOCK_SIZE];
+    while state.tick() < num_ticks {
+        let block = &mut block[..BLOCK_SIZE.min(num_ticks - state.tick())];
+        player.play_block(&state, block);
+        write_block(block, outputs);
+        state.increment_by(block.len());
+    }
+}
+
+fn write_block(block: &[Playable], outputs: &mut [Box<dyn Output>]) {
+    for output in outputs.iter_mut() {
+        for playable in block {
+            output.write(*playable);
         }
-        state.increment();
     }
 }
 
@@ -36,6 +100,51 @@ fn get_from_config(config_path: String) -> Result<(Consts, Vec<Box<dyn Output>>)
     let consts = Consts::default()?;
     let mut spec = read::path_to_spec(Path::new(&config_path), ReadType::Yaml)?;
     let consts: Consts = spec.consume("consts", &consts)?;
+    let mut spec = expand_spec(spec, &consts)?;
     let outputs: Vec<Box<dyn Output>> = spec.consume("outputs", &consts)?;
     Ok((consts, outputs))
 }
+
+fn get_consts_from_config(config_path: String) -> Result<Consts> {
+    let consts = Consts::default()?;
+    let mut spec = read::path_to_spec(Path::new(&config_path), ReadType::Yaml)?;
+    spec.consume("consts", &consts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use players::Linear;
+
+    use std::sync::Mutex;
+
+    struct Recorder {
+        played: Arc<Mutex<Vec<Playable>>>,
+    }
+
+    impl Output for Recorder {
+        fn write(&mut self, playable: Playable) {
+            self.played.lock().unwrap().push(playable);
+        }
+    }
+
+    #[test]
+    fn test_render_duration() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let played = Arc::new(Mutex::new(Vec::new()));
+        let mut outputs: Vec<Box<dyn Output>> = vec![Box::new(Recorder {
+            played: played.clone(),
+        })];
+        render(
+            &mut Linear::player(1),
+            &mut outputs,
+            &Time::Beats(1.0),
+            consts,
+        );
+
+        let played = played.lock().unwrap();
+        assert_eq!(played.len(), 44100 / 2);
+        assert_eq!(played[0], Playable::new(0.0));
+        assert_eq!(played[100], Playable::new(100.0));
+    }
+}
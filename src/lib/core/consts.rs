@@ -1,19 +1,33 @@
+use core::meter::{MeterChange, MeterMap};
 use core::spec::{FromValue, Spec, Value};
+use core::tempo::{TempoChange, TempoMap};
 use core::Time;
 use error::*;
 
+use std::path::PathBuf;
+
 /// Constants in the composition
 pub struct Consts {
     /// How many samples are in a second in the output audio
     pub sample_hz: f64,
-    /// How many beats are in a minute (i.e. bpm)
+    /// How many beats are in a minute (i.e. bpm) at the start of the
+    /// composition
     pub beats_per_minute: f64,
-    /// How many beats are in a bar (i.e. time signature)
+    /// How many beats are in a bar (i.e. time signature) at the start of the
+    /// composition
     pub beats_per_bar: f64,
     /// How loud sound is by default
     pub loudness_factor: f64,
     /// How often to reload the input configuration file
     pub reload_time: Time,
+    /// Frequency of A4, which all note names are tuned relative to
+    pub tuning_hz: f64,
+    /// Directory that sample paths are relative to
+    pub sample_dir: PathBuf,
+    /// How the tempo changes throughout the composition
+    pub tempo: TempoMap,
+    /// How the time signature changes throughout the composition
+    pub meter: MeterMap,
 }
 
 impl Consts {
@@ -24,6 +38,8 @@ impl Consts {
         beats_per_bar: f64,
         loudness_factor: f64,
         reload_time: Time,
+        tuning_hz: f64,
+        sample_dir: PathBuf,
     ) -> Result<Self> {
         Ok(Consts {
             sample_hz,
@@ -31,12 +47,24 @@ impl Consts {
             beats_per_bar,
             loudness_factor,
             reload_time,
+            tuning_hz,
+            sample_dir,
+            tempo: TempoMap::constant(beats_per_minute),
+            meter: MeterMap::constant(beats_per_bar),
         })
     }
 
     /// The default values for the constants
     pub fn default() -> Result<Self> {
-        Consts::new(44100.0, 120.0, 4.0, 0.3, Time::Ticks(0))
+        Consts::new(
+            44100.0,
+            120.0,
+            4.0,
+            0.3,
+            Time::Ticks(0),
+            440.0,
+            PathBuf::new(),
+        )
     }
 }
 
@@ -46,13 +74,24 @@ impl FromValue for Consts {
     }
     fn from_value(value: Value, consts: &Consts) -> Result<Consts> {
         let mut spec: Spec = value.into_type(consts)?;
-        let consts = Consts::new(
+        let mut consts = Consts::new(
             spec.consume_with_default("sample-hz", consts.sample_hz, consts)?,
             spec.consume_with_default("beats-per-minute", consts.beats_per_minute, consts)?,
             spec.consume_with_default("beats-per-bar", consts.beats_per_bar, consts)?,
             spec.consume_with_default("loudness-factor", consts.loudness_factor, consts)?,
             spec.consume_with_default("reload-time", Time::zero(), consts)?,
+            spec.consume_with_default("tuning-hz", consts.tuning_hz, consts)?,
+            spec.consume_optional::<String>("sample-dir", consts)?
+                .map(PathBuf::from)
+                .unwrap_or_else(|| consts.sample_dir.clone()),
         )?;
+        // The meter is needed to place tempo changes given in bars
+        let meter_changes: Vec<MeterChange> =
+            spec.consume_with_default("meter", vec![], &consts)?;
+        consts.meter = MeterMap::new(consts.beats_per_bar, meter_changes)?;
+        let tempo_changes: Vec<TempoChange> =
+            spec.consume_with_default("tempo", vec![], &consts)?;
+        consts.tempo = TempoMap::new(consts.beats_per_minute, tempo_changes)?;
         spec.ensure_all_used()?;
         Ok(consts)
     }
@@ -6,6 +6,17 @@ pub trait Input: Tree + Send + Sync {
     #[allow(missing_docs)]
     fn get(&mut self, state: &State) -> f64;
 
+    /// Fill a block with consecutive input values, starting at `state`
+    ///
+    /// Defaults to calling `get` for every tick in the block.
+    fn get_block(&mut self, state: &State, block: &mut [f64]) {
+        let mut state = state.clone();
+        for value in block.iter_mut() {
+            *value = self.get(&state);
+            state.increment();
+        }
+    }
+
     /// Casts float input to a boolean input. Is false if == 0
     fn get_bool(&mut self, state: &State) -> bool {
         self.get(state) != 0.0
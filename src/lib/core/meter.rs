@@ -0,0 +1,164 @@
+use core::spec::{FromValue, Spec, Value};
+use core::Consts;
+use error::*;
+
+/// A change in time signature at the start of a bar
+///
+/// In specs, has a `bar` number counting from one, and a `beats-per-bar`.
+#[derive(Clone, Debug)]
+pub struct MeterChange {
+    /// How many bars from the start of the composition the change happens
+    pub bar: usize,
+    /// How many beats are in each bar after the change
+    pub beats_per_bar: f64,
+}
+
+/// Bars with the same time signature
+#[derive(Clone, Debug)]
+struct Segment {
+    start_bar: f64,
+    start_beat: f64,
+    beats_per_bar: f64,
+}
+
+/// The time signatures throughout a composition
+#[derive(Clone, Debug)]
+pub struct MeterMap {
+    /// Ordered by start, the first segment always starts at zero
+    segments: Vec<Segment>,
+}
+
+impl MeterMap {
+    /// Create a meter map that starts at `beats_per_bar` and then follows
+    /// `changes`
+    pub fn new(beats_per_bar: f64, mut changes: Vec<MeterChange>) -> Result<MeterMap> {
+        changes.sort_by_key(|change| change.bar);
+        if changes.is_empty() || changes[0].bar > 0 {
+            changes.insert(
+                0,
+                MeterChange {
+                    bar: 0,
+                    beats_per_bar,
+                },
+            );
+        }
+        if let Some(change) = changes.iter().find(|change| change.beats_per_bar <= 0.0) {
+            bail!(ErrorKind::SpecError(format!(
+                "Beats per bar must be positive: {}",
+                change.beats_per_bar
+            )));
+        }
+
+        let mut segments: Vec<Segment> = Vec::with_capacity(changes.len());
+        for change in changes {
+            let start_bar = change.bar as f64;
+            let start_beat = match segments.last() {
+                Some(previous) => {
+                    previous.start_beat + (start_bar - previous.start_bar) * previous.beats_per_bar
+                }
+                None => 0.0,
+            };
+            segments.push(Segment {
+                start_bar,
+                start_beat,
+                beats_per_bar: change.beats_per_bar,
+            });
+        }
+        Ok(MeterMap { segments })
+    }
+
+    /// Create a meter map that never changes
+    pub fn constant(beats_per_bar: f64) -> MeterMap {
+        MeterMap {
+            segments: vec![Segment {
+                start_bar: 0.0,
+                start_beat: 0.0,
+                beats_per_bar,
+            }],
+        }
+    }
+
+    /// Get how many beats from the start of the composition a bar is
+    pub fn bars_to_beats(&self, bars: f64) -> f64 {
+        let segment = self
+            .segments
+            .iter()
+            .rev()
+            .find(|segment| segment.start_bar <= bars)
+            .unwrap_or(&self.segments[0]);
+        segment.start_beat + (bars - segment.start_bar) * segment.beats_per_bar
+    }
+
+    /// Get how many bars from the start of the composition a beat is
+    pub fn beats_to_bars(&self, beats: f64) -> f64 {
+        let segment = self
+            .segments
+            .iter()
+            .rev()
+            .find(|segment| segment.start_beat <= beats)
+            .unwrap_or(&self.segments[0]);
+        segment.start_bar + (beats - segment.start_beat) / segment.beats_per_bar
+    }
+
+    /// Get the most beats in any bar in the composition
+    pub fn longest_beats_per_bar(&self) -> f64 {
+        self.segments
+            .iter()
+            .map(|segment| segment.beats_per_bar)
+            .fold(0.0, f64::max)
+    }
+}
+
+impl FromValue for MeterChange {
+    fn name() -> String {
+        "meter-change".into()
+    }
+
+    fn from_value(value: Value, consts: &Consts) -> Result<MeterChange> {
+        let mut spec: Spec = value.into_type(consts)?;
+        let bar: i32 = spec.consume("bar", consts)?;
+        if bar < 1 {
+            bail!(ErrorKind::SpecError(format!(
+                "Bar numbers start from one, got {}",
+                bar
+            )));
+        }
+        let beats_per_bar = spec.consume("beats-per-bar", consts)?;
+        spec.ensure_all_used()?;
+        Ok(MeterChange {
+            bar: bar as usize - 1,
+            beats_per_bar,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_changes() {
+        let meter = MeterMap::new(
+            4.0,
+            vec![MeterChange {
+                bar: 2,
+                beats_per_bar: 3.0,
+            }],
+        )
+        .unwrap();
+        assert_eq!(meter.bars_to_beats(1.0), 4.0);
+        assert_eq!(meter.bars_to_beats(2.0), 8.0);
+        assert_eq!(meter.bars_to_beats(4.0), 14.0);
+        assert_eq!(meter.beats_to_bars(14.0), 4.0);
+        assert_eq!(meter.beats_to_bars(6.0), 1.5);
+        assert_eq!(meter.longest_beats_per_bar(), 4.0);
+        assert!(MeterMap::new(
+            4.0,
+            vec![MeterChange {
+                bar: 2,
+                beats_per_bar: 0.0,
+            }]
+        )
+        .is_err());
+    }
+}
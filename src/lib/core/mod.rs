@@ -7,26 +7,35 @@
 //! through [`State`](struct.State.html)s. `Player`s can
 //! be controlled through [`input`](input/) traits.
 
+mod chord;
 pub mod composer;
 mod composition;
 mod consts;
 mod input;
+mod meter;
 mod output;
+mod pitch;
 mod playable;
 mod player;
 mod reload_player;
 #[macro_use]
 pub mod spec;
 mod state;
+mod tempo;
 mod time;
 pub mod tree;
 
+pub use self::chord::Chord;
 pub use self::composition::Composition;
 pub use self::consts::Consts;
 pub use self::input::Input;
+pub use self::meter::{MeterChange, MeterMap};
 pub use self::output::Output;
+pub use self::pitch::Pitch;
 pub use self::playable::Playable;
+pub use self::playable::NUM_CHANNELS;
 pub use self::player::Player;
 pub use self::reload_player::get_reloading_player;
 pub use self::state::State;
+pub use self::tempo::{TempoChange, TempoMap};
 pub use self::time::Time;
@@ -0,0 +1,161 @@
+use core::spec::{FromValue, Value};
+use core::Consts;
+use error::*;
+use std::str::FromStr;
+
+/// MIDI number of A4, which is tuned to `Consts::tuning_hz`
+const TUNING_MIDI: f64 = 69.0;
+
+/// A pitch in different measurements
+///
+/// Can be parsed from:
+/// - Note names in scientific pitch notation, e.g. `a4`, `c#5` or `Bb3`
+/// - MIDI numbers, e.g. `69`
+/// - Raw frequencies, e.g. `440 hz`
+///
+/// Note names and MIDI numbers can be followed by an offset in cents, e.g.
+/// `a4 +15 cents`. Floats in specs are frequencies, and integers are MIDI
+/// numbers.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pitch {
+    /// Frequency in Hz
+    Hz(f64),
+    /// MIDI number, where fractions are between semitones
+    Midi(f64),
+}
+
+impl Pitch {
+    /// Get the frequency of the pitch, using the tuning in `consts`
+    pub fn to_hz(&self, consts: &Consts) -> f64 {
+        match self {
+            Pitch::Hz(hz) => *hz,
+            Pitch::Midi(midi) => consts.tuning_hz * 2_f64.powf((midi - TUNING_MIDI) / 12.0),
+        }
+    }
+
+    /// Get the MIDI number of the pitch, using the tuning in `consts`
+    pub fn to_midi(&self, consts: &Consts) -> f64 {
+        match self {
+            Pitch::Hz(hz) => TUNING_MIDI + 12.0 * (hz / consts.tuning_hz).log2(),
+            Pitch::Midi(midi) => *midi,
+        }
+    }
+
+    /// Get the pitch moved by a number of semitones
+    pub fn transpose(&self, semitones: f64) -> Pitch {
+        match self {
+            Pitch::Hz(hz) => Pitch::Hz(hz * 2_f64.powf(semitones / 12.0)),
+            Pitch::Midi(midi) => Pitch::Midi(midi + semitones),
+        }
+    }
+
+    fn from_note_name(string: &str) -> Result<f64> {
+        let mut chars = string.chars();
+        let semitone = match chars.next().map(|c| c.to_ascii_lowercase()) {
+            Some('c') => 0,
+            Some('d') => 2,
+            Some('e') => 4,
+            Some('f') => 5,
+            Some('g') => 7,
+            Some('a') => 9,
+            Some('b') => 11,
+            _ => bail!(ErrorKind::SpecError(format!(
+                "Unrecognized note name: {}",
+                string
+            ))),
+        };
+        let rest = chars.as_str();
+        let accidentals = rest
+            .chars()
+            .take_while(|c| *c == '#' || *c == 'b')
+            .collect::<Vec<_>>();
+        let accidental: i32 = accidentals
+            .iter()
+            .map(|c| if *c == '#' { 1 } else { -1 })
+            .sum();
+        let octave: i32 = rest[accidentals.len()..]
+            .parse()
+            .chain_err(|| format!("Failed to parse octave of note: {}", string))?;
+        Ok(f64::from(12 * (octave + 1) + semitone + accidental))
+    }
+}
+
+impl FromStr for Pitch {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Pitch> {
+        match string.trim().split(' ').collect::<Vec<_>>().as_slice() {
+            [number, "hz"] | [number, "Hz"] => Ok(Pitch::Hz(
+                number.parse().chain_err(|| "Failed to parse frequency")?,
+            )),
+            [pitch, cents, "cents"] => {
+                let cents: f64 = cents.parse().chain_err(|| "Failed to parse cents")?;
+                Ok(pitch.parse::<Pitch>()?.transpose(cents / 100.0))
+            }
+            [pitch] => match pitch.parse::<f64>() {
+                Ok(midi) => Ok(Pitch::Midi(midi)),
+                Err(_) => Ok(Pitch::Midi(Pitch::from_note_name(pitch)?)),
+            },
+            _ => Err(ErrorKind::SpecError(format!("Unrecognized pitch: {}", string)).into()),
+        }
+    }
+}
+
+impl FromValue for Pitch {
+    fn name() -> String {
+        "pitch".into()
+    }
+    fn from_value(value: Value, consts: &Consts) -> Result<Pitch> {
+        match value {
+            Value::Float(hz) => Ok(Pitch::Hz(hz)),
+            Value::Int(midi) => Ok(Pitch::Midi(f64::from(midi))),
+            value => {
+                let string: String = value.into_type(consts)?;
+                string.parse()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn to_hz(string: &str) -> f64 {
+        let consts = Consts::default().unwrap();
+        string.parse::<Pitch>().unwrap().to_hz(&consts)
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 0.01, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_close(to_hz("a4"), 440.0);
+        assert_close(to_hz("A5"), 880.0);
+        assert_close(to_hz("c5"), 523.25);
+        assert_close(to_hz("c#5"), 554.37);
+        assert_close(to_hz("Db5"), 554.37);
+        assert_close(to_hz("Bb3"), 233.08);
+        assert_close(to_hz("c-1"), 8.18);
+        assert_close(to_hz("69"), 440.0);
+        assert_close(to_hz("60.5"), 269.29);
+        assert_close(to_hz("a4 +100 cents"), to_hz("a#4"));
+        assert_close(to_hz("a4 -50 cents"), 427.47);
+        assert_close(to_hz("523.25 hz"), 523.25);
+        assert!("h4".parse::<Pitch>().is_err());
+        assert!("a".parse::<Pitch>().is_err());
+        assert!("a4 cents".parse::<Pitch>().is_err());
+    }
+
+    #[test]
+    fn test_tuning() {
+        let mut consts = Consts::default().unwrap();
+        consts.tuning_hz = 432.0;
+        assert_close(Pitch::Midi(69.0).to_hz(&consts), 432.0);
+        assert_close(Pitch::Midi(81.0).to_hz(&consts), 864.0);
+        assert_close(Pitch::Hz(440.0).to_hz(&consts), 440.0);
+        assert_close(Pitch::Hz(864.0).to_midi(&consts), 81.0);
+    }
+}
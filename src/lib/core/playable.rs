@@ -1,56 +1,101 @@
 //! Values that can be played
 
-use std::i32;
 use std::iter::Sum;
 use std::ops;
 
-/// A single data point in a sound wave
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// Number of channels in every [`Playable`](struct.Playable.html)
+pub const NUM_CHANNELS: usize = 2;
+
+/// A single frame in a sound wave, holding a data point for every channel
+///
+/// Values are nominally in [-1, 1], but can exceed that range while being
+/// processed. They are only clipped and converted when written to an
+/// [`Output`](trait.Output.html).
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Playable {
-    value: i32,
+    values: [f64; NUM_CHANNELS],
 }
 
 impl Playable {
-    #[allow(missing_docs)]
-    pub fn new(value: i32) -> Self {
-        Playable { value }
+    /// Create a mono playable, with the same value in every channel
+    pub fn new(value: f64) -> Self {
+        Playable {
+            values: [value; NUM_CHANNELS],
+        }
+    }
+
+    /// Create a playable with a value for each channel
+    pub fn from_channels(values: [f64; NUM_CHANNELS]) -> Self {
+        Playable { values }
+    }
+
+    /// Create a playable from a left and right value
+    pub fn stereo(left: f64, right: f64) -> Self {
+        Playable::from_channels([left, right])
+    }
+
+    /// Get the mono mix of all channels
+    pub fn get_value(self) -> f64 {
+        self.values.iter().sum::<f64>() / NUM_CHANNELS as f64
+    }
+
+    /// Get the value of a single channel
+    pub fn get_channel(self, channel: usize) -> f64 {
+        self.values[channel]
     }
 
-    #[allow(missing_docs)]
-    pub fn get_value(self) -> i32 {
-        self.value
+    /// Get the value to write to `channel` of an output with `num_channels`
+    /// channels
+    ///
+    /// Mono outputs get the mix of all channels.
+    pub fn get_output_value(self, channel: usize, num_channels: usize) -> f64 {
+        if num_channels == 1 {
+            self.get_value()
+        } else {
+            self.get_channel(channel)
+        }
     }
 
     /// Get the zero value for the playable
     pub fn zero() -> Playable {
-        Playable::new(0)
+        Playable::new(0.0)
+    }
+
+    fn map(self, f: impl Fn(f64) -> f64) -> Playable {
+        let mut values = self.values;
+        for value in values.iter_mut() {
+            *value = f(*value);
+        }
+        Playable { values }
+    }
+
+    fn zip_map(self, other: Playable, f: impl Fn(f64, f64) -> f64) -> Playable {
+        let mut values = self.values;
+        for (value, other_value) in values.iter_mut().zip(other.values.iter()) {
+            *value = f(*value, *other_value);
+        }
+        Playable { values }
     }
 }
 
 impl ops::Add for Playable {
     type Output = Playable;
     fn add(self, other: Self) -> Self {
-        // Perform addition in u64, clamp to i32 range, cast to i32
-        // TODO: Can we make this faster?
-        Playable::new(
-            (i64::from(self.value) + i64::from(other.value))
-                .max(i64::from(i32::MIN))
-                .min(i64::from(i32::MAX)) as i32,
-        )
+        self.zip_map(other, |a, b| a + b)
     }
 }
 
 impl ops::Mul for Playable {
     type Output = Playable;
     fn mul(self, other: Self) -> Self {
-        Playable::new(self.value * other.value)
+        self.zip_map(other, |a, b| a * b)
     }
 }
 
 impl ops::Mul<f64> for Playable {
     type Output = Playable;
     fn mul(self, other: f64) -> Self {
-        Playable::new((f64::from(self.value) * other) as i32)
+        self.map(|value| value * other)
     }
 }
 
@@ -59,7 +104,7 @@ impl Sum for Playable {
     where
         I: Iterator<Item = Self>,
     {
-        iter.fold(Playable::new(0), ops::Add::add)
+        iter.fold(Playable::zero(), ops::Add::add)
     }
 }
 
@@ -70,10 +115,21 @@ mod test {
     #[test]
     fn test_sum_playable() {
         assert_eq!(
-            Playable::new(6),
-            vec![Playable::new(1), Playable::new(2), Playable::new(3)]
+            Playable::new(6.0),
+            vec![Playable::new(1.0), Playable::new(2.0), Playable::new(3.0)]
                 .into_iter()
                 .sum(),
         );
     }
+
+    #[test]
+    fn test_channels() {
+        let playable = Playable::stereo(1.0, -0.5) + Playable::new(0.25);
+        assert_eq!(playable.get_channel(0), 1.25);
+        assert_eq!(playable.get_channel(1), -0.25);
+        assert_eq!(playable.get_value(), 0.5);
+        assert_eq!(playable.get_output_value(1, 1), 0.5);
+        assert_eq!(playable.get_output_value(1, 2), -0.25);
+        assert_eq!((playable * 0.5).get_channel(0), 0.625);
+    }
 }
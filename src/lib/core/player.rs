@@ -8,4 +8,16 @@ use core::State;
 pub trait Player: Tree + Send + Sync {
     /// Create the next `Playable`, given some progress through the composition
     fn play(&mut self, state: &State) -> Playable;
+
+    /// Fill a block with consecutive `Playable`s, starting at `state`
+    ///
+    /// Defaults to calling `play` for every tick in the block. Implementors
+    /// should override this when they can produce a block faster.
+    fn play_block(&mut self, state: &State, block: &mut [Playable]) {
+        let mut state = state.clone();
+        for playable in block.iter_mut() {
+            *playable = self.play(&state);
+            state.increment();
+        }
+    }
 }
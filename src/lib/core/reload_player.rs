@@ -1,3 +1,4 @@
+use core::spec::expand_spec;
 use core::spec::read;
 use core::spec::read::ReadType;
 use core::spec::Value;
@@ -89,5 +90,6 @@ fn load_spec(spec_path: &str, current_yaml_hash: &mut u64) -> Result<Option<Stri
 
 fn load_player(yaml_str: String, read_type: ReadType, consts: &Consts) -> Result<Box<dyn Player>> {
     let spec = read::string_to_spec(yaml_str, read_type)?;
+    let spec = expand_spec(spec, consts)?;
     Value::Spec(spec).into_type(consts)
 }
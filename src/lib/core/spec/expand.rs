@@ -0,0 +1,307 @@
+//! Expands macros in specs, before they are created as types
+//!
+//! A `map` spec creates a list by filling in a template for every element in
+//! another list:
+//!
+//! ```yaml
+//! name: map
+//! list: [a4, c5, e5]
+//! fn:
+//!   name: wave
+//!   frequency: $1
+//! ```
+//!
+//! `$1`, `$2`... are replaced by the items of each element when the element is
+//! a list, otherwise `$1` is replaced by the element itself. Placeholders can
+//! also be part of a string, e.g. `$1 maj`. Nested maps are expanded first, so
+//! placeholders always refer to the innermost map.
+//!
+//! The list can also be a spec that generates a list, which can also be used
+//! anywhere else a list is expected:
+//! - `chord` generates the frequencies of a [`Chord`](../struct.Chord.html)
+//! - `timeline-multi` generates a
+//!   [`Timeline`](../../inputs/struct.Timeline.html) for every line in its
+//!   `events`, each lasting `event-duration` and retriggering if `retrigger`
+//!   is given
+
+use core::spec::{Spec, Value};
+use core::Chord;
+use core::Consts;
+use error::*;
+use inputs::Timeline;
+
+use std::collections::HashMap;
+
+/// Expand all macros in a spec
+pub fn expand_spec(spec: Spec, consts: &Consts) -> Result<Spec> {
+    match expand_value(Value::Spec(spec), consts)? {
+        Value::Spec(spec) => Ok(spec),
+        _ => bail!(ErrorKind::SpecError(
+            "Top level spec can't expand to a list".into()
+        )),
+    }
+}
+
+fn expand_value(value: Value, consts: &Consts) -> Result<Value> {
+    match value {
+        Value::Spec(spec) => {
+            let mut values = HashMap::new();
+            for (value_name, value) in spec.values {
+                let expanded = expand_value(value, consts)
+                    .chain_err(|| format!("Failed to expand field {}", value_name))?;
+                values.insert(value_name, expanded);
+            }
+            let spec = Spec::new(values);
+            match spec.get::<String>("name") {
+                Ok(name) if name == "map" => expand_map(spec, consts),
+                _ => Ok(Value::Spec(spec)),
+            }
+        }
+        Value::List(list) => Ok(Value::List(
+            list.into_iter()
+                .map(|value| expand_value(value, consts))
+                .collect::<Result<_>>()?,
+        )),
+        value => Ok(value),
+    }
+}
+
+fn expand_map(mut spec: Spec, consts: &Consts) -> Result<Value> {
+    spec.consume::<String>("name", consts)?;
+    let list: Value = spec.consume("list", consts)?;
+    let template: Value = spec.consume("fn", consts)?;
+    spec.ensure_all_used()?;
+
+    let list = match list {
+        Value::List(list) => list,
+        Value::Spec(spec) => {
+            generate_list(spec, consts).chain_err(|| "Failed to generate list for map")?
+        }
+        list => bail!(ErrorKind::SpecError(format!(
+            "Map list must be a list or a spec that generates a list, got {:?}",
+            list
+        ))),
+    };
+
+    list.into_iter()
+        .enumerate()
+        .map(|(i, element)| {
+            let arguments = match element {
+                Value::List(items) => items,
+                element => vec![element],
+            };
+            fill_in(template.clone(), &arguments)
+                .chain_err(|| format!("Failed to fill in map template for element {}", i))
+        })
+        .collect::<Result<_>>()
+        .map(Value::List)
+}
+
+/// Generate a list of values from a spec
+pub fn generate_list(mut spec: Spec, consts: &Consts) -> Result<Vec<Value>> {
+    let name: String = spec.consume("name", consts)?;
+    let list = match name.as_str() {
+        "chord" => {
+            let chord: Chord = spec.consume("chord", consts)?;
+            chord
+                .frequencies(consts)
+                .into_iter()
+                .map(Value::Float)
+                .collect()
+        }
+        "timeline-multi" => {
+            let events: String = spec.consume("events", consts)?;
+            let event_duration: Value = spec.consume("event-duration", consts)?;
+            let retrigger: Option<Value> = spec.consume_optional("retrigger", consts)?;
+            Timeline::split_grid(&events)?
+                .into_iter()
+                .map(|row| {
+                    let mut timeline = Spec::empty()
+                        .with("name".into(), "timeline".to_string())
+                        .with("events".into(), row)
+                        .with("event-duration".into(), event_duration.clone());
+                    if let Some(retrigger) = &retrigger {
+                        timeline.put("retrigger".into(), retrigger.clone());
+                    }
+                    Value::Spec(timeline)
+                })
+                .collect()
+        }
+        name => bail!(ErrorKind::SpecError(format!(
+            "Unrecognized list generator: {}",
+            name
+        ))),
+    };
+    spec.ensure_all_used()?;
+    Ok(list)
+}
+
+/// Replace the placeholders in a template with arguments
+fn fill_in(template: Value, arguments: &[Value]) -> Result<Value> {
+    match template {
+        Value::Str(string) => fill_in_string(string, arguments),
+        Value::Spec(spec) => {
+            let mut values = HashMap::new();
+            for (value_name, value) in spec.values {
+                values.insert(value_name, fill_in(value, arguments)?);
+            }
+            Ok(Value::Spec(Spec::new(values)))
+        }
+        Value::List(list) => Ok(Value::List(
+            list.into_iter()
+                .map(|value| fill_in(value, arguments))
+                .collect::<Result<_>>()?,
+        )),
+        value => Ok(value),
+    }
+}
+
+fn fill_in_string(mut string: String, arguments: &[Value]) -> Result<Value> {
+    // A string that's only a placeholder is replaced by the argument, keeping
+    // its type
+    if let Some(index) = string.trim().strip_prefix('$') {
+        if let Ok(index) = index.parse::<usize>() {
+            return get_argument(arguments, index).cloned();
+        }
+    }
+
+    // Replace the highest indices first, so that `$1` doesn't match `$10`
+    for index in (1..=arguments.len()).rev() {
+        let placeholder = format!("${}", index);
+        if string.contains(&placeholder) {
+            let argument = match get_argument(arguments, index)? {
+                Value::Str(argument) => argument.clone(),
+                Value::Int(argument) => argument.to_string(),
+                Value::Float(argument) => argument.to_string(),
+                Value::Bool(argument) => argument.to_string(),
+                argument => bail!(ErrorKind::SpecError(format!(
+                    "Can't fill in {:?} as part of a string",
+                    argument
+                ))),
+            };
+            string = string.replace(&placeholder, &argument);
+        }
+    }
+    Ok(Value::Str(string))
+}
+
+fn get_argument(arguments: &[Value], index: usize) -> Result<&Value> {
+    if index == 0 || index > arguments.len() {
+        bail!(ErrorKind::SpecError(format!(
+            "Placeholder ${} is out of range, element has {} values",
+            index,
+            arguments.len()
+        )));
+    }
+    Ok(&arguments[index - 1])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::spec::read::yaml_string_to_spec;
+    use core::Input;
+    use core::State;
+
+    use error_chain::ChainedError;
+
+    use std::sync::Arc;
+
+    fn expand(yaml: &str) -> Result<Spec> {
+        let consts = Consts::default().unwrap();
+        expand_spec(yaml_string_to_spec(yaml.into()).unwrap(), &consts)
+    }
+
+    fn children(mut spec: Spec) -> Vec<Value> {
+        spec.consume("children", &Consts::default().unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_map() {
+        let spec = expand(
+            "
+            name: combiner
+            children:
+              name: map
+              list: [[a4, 0.5], [c5, 0.25]]
+              fn:
+                name: volume
+                input: $2
+                child:
+                  name: wave
+                  frequency: $1
+                  label: note $1
+            ",
+        )
+        .unwrap();
+        let mut children = children(spec);
+        assert_eq!(children.len(), 2);
+        match &mut children[1] {
+            Value::Spec(child) => {
+                assert_eq!(child.get::<f64>("input").unwrap(), &0.25);
+                let wave: &mut Spec = child.get_mut("child").unwrap();
+                assert_eq!(wave.get::<String>("frequency").unwrap(), "c5");
+                assert_eq!(wave.get::<String>("label").unwrap(), "note c5");
+            }
+            child => panic!("Expected spec, got {:?}", child),
+        }
+    }
+
+    #[test]
+    fn test_map_chord() {
+        let spec = expand(
+            "
+            name: combiner
+            children:
+              name: map
+              list:
+                name: chord
+                chord: a4 maj
+              fn:
+                name: wave
+                frequency: $1
+            ",
+        )
+        .unwrap();
+        assert_eq!(children(spec).len(), 3);
+    }
+
+    #[test]
+    fn test_timeline_multi() {
+        let consts = Consts::default().unwrap();
+        let timelines = |events: &str| -> Result<Vec<Box<dyn Input>>> {
+            Value::Spec(
+                Spec::empty()
+                    .with("name".into(), "timeline-multi".to_string())
+                    .with("event-duration".into(), "1 beats".to_string())
+                    .with("events".into(), events.to_string()),
+            )
+            .into_type(&consts)
+        };
+        assert_eq!(timelines("X__X\n_X_X\n__XX\n").unwrap().len(), 3);
+        let error = timelines("X__X\n_X_X\n__XXX\n").err().unwrap();
+        assert!(format!("{}", error.display_chain()).contains("Row 3"));
+
+        // Retriggering is passed on to every timeline
+        let mut retriggered: Vec<Box<dyn Input>> = Value::Spec(
+            Spec::empty()
+                .with("name".into(), "timeline-multi".to_string())
+                .with("event-duration".into(), "1 ticks".to_string())
+                .with("events".into(), "XX".to_string())
+                .with("retrigger".into(), true),
+        )
+        .into_type(&consts)
+        .unwrap();
+        let state = State::initial(Arc::new(consts));
+        assert_eq!(retriggered[0].get(&state.with_tick(1)), 0.0);
+    }
+
+    #[test]
+    fn test_map_errors() {
+        assert!(expand("{name: map, list: [1, 2], fn: {value: $2}}").is_err());
+        assert!(expand("{name: map, list: 1, fn: {value: $1}}").is_err());
+        assert!(expand("{name: map, list: {name: chords}, fn: {value: $1}}").is_err());
+        assert!(expand("{name: list, fn: {name: map, list: [1], fn: [$1]}}").is_ok());
+    }
+}
@@ -1,3 +1,4 @@
+use core::spec::generate_list;
 use core::spec::Spec;
 use core::spec::Value;
 use core::Consts;
@@ -104,12 +105,25 @@ impl<T: FromValue> FromValue for Vec<T> {
     }
 
     fn from_value(value: Value, consts: &Consts) -> Result<Vec<T>> {
-        if let Value::List(list) = value {
-            list.into_iter()
-                .map(|v| T::from_value(v, consts))
-                .collect::<Result<_>>()
-        } else {
-            bail!(ErrorKind::SpecError("Expected list type".into()));
-        }
+        let list = match value {
+            Value::List(list) => list,
+            Value::Spec(spec) => generate_list(spec, consts)?,
+            _ => bail!(ErrorKind::SpecError("Expected list type".into())),
+        };
+        list.into_iter()
+            .map(|v| T::from_value(v, consts))
+            .collect::<Result<_>>()
+    }
+}
+
+/// Only useful as a field with a default of `None`, since a given value is
+/// always `Some`
+impl<T: FromValue> FromValue for Option<T> {
+    fn name() -> String {
+        T::name() + "?"
+    }
+
+    fn from_value(value: Value, consts: &Consts) -> Result<Option<T>> {
+        T::from_value(value, consts).map(Some)
     }
 }
@@ -9,10 +9,12 @@ use std::collections::HashMap;
 mod spec_field;
 #[macro_use]
 mod super_spec_type;
+mod expand;
 mod from_value;
 pub mod read;
 mod spec_type;
 
+pub use self::expand::{expand_spec, generate_list};
 pub use self::from_value::{FromPrimitiveValue, FromValue};
 pub use self::spec_field::{SpecField, SpecFieldDescription};
 pub use self::spec_type::{SpecType, SpecTypeDescription};
@@ -5,6 +5,7 @@ use core::Consts;
 use std::sync::Arc;
 
 /// Used to keep track of the progress through a composition
+#[derive(Clone)]
 pub struct State {
     /// How far through, in 1000ths of a step, we are through the composition
     pub milli_tick: usize,
@@ -31,6 +32,11 @@ impl State {
         self.milli_tick += 1000;
     }
 
+    /// Step forward several states in the composition
+    pub fn increment_by(&mut self, ticks: usize) {
+        self.milli_tick += ticks * 1000;
+    }
+
     /// Get a copy of the state with a custom tick value
     pub fn with_tick(&self, tick: usize) -> Self {
         self.with_milli_tick(tick * 1000)
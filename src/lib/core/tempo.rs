@@ -0,0 +1,258 @@
+use core::spec::{FromValue, Spec, Value};
+use core::Consts;
+use core::Time;
+use error::*;
+
+/// A change in tempo at a position in the composition
+///
+/// In specs, has an `at` position in beats or bars, a `beats-per-minute`, and
+/// a `transition` of either `step` or `ramp`.
+#[derive(Clone, Debug)]
+pub struct TempoChange {
+    /// How many beats from the start of the composition the change happens
+    pub beat: f64,
+    /// The tempo after the change
+    pub beats_per_minute: f64,
+    /// If true, the tempo ramps from the previous change to this change,
+    /// otherwise it steps to the new tempo at the change
+    pub ramp: bool,
+}
+
+/// A stretch of time with a constant or linearly changing tempo
+#[derive(Clone, Debug)]
+struct Segment {
+    start_beat: f64,
+    start_seconds: f64,
+    beats_per_minute: f64,
+    /// How much the tempo changes for every beat in the segment
+    ramp_per_beat: f64,
+}
+
+impl Segment {
+    /// Get how many seconds `beats` beats lasts from the start of the segment
+    fn beats_to_seconds(&self, beats: f64) -> f64 {
+        if self.ramp_per_beat == 0.0 {
+            beats * 60.0 / self.beats_per_minute
+        } else {
+            let end_beats_per_minute = self.beats_per_minute + self.ramp_per_beat * beats;
+            60.0 / self.ramp_per_beat * (end_beats_per_minute / self.beats_per_minute).ln()
+        }
+    }
+
+    /// Get how many beats last `seconds` seconds from the start of the segment
+    fn seconds_to_beats(&self, seconds: f64) -> f64 {
+        if self.ramp_per_beat == 0.0 {
+            seconds * self.beats_per_minute / 60.0
+        } else {
+            self.beats_per_minute * ((self.ramp_per_beat * seconds / 60.0).exp() - 1.0)
+                / self.ramp_per_beat
+        }
+    }
+}
+
+/// The tempo throughout a composition
+#[derive(Clone, Debug)]
+pub struct TempoMap {
+    /// Ordered by start, the first segment always starts at zero
+    segments: Vec<Segment>,
+}
+
+impl TempoMap {
+    /// Create a tempo map that starts at `beats_per_minute` and then follows
+    /// `changes`
+    pub fn new(beats_per_minute: f64, mut changes: Vec<TempoChange>) -> Result<TempoMap> {
+        if let Some(change) = changes.iter().find(|change| !change.beat.is_finite()) {
+            bail!(ErrorKind::SpecError(format!(
+                "Tempo change must be at a finite position: {}",
+                change.beat
+            )));
+        }
+        changes.sort_by(|a, b| a.beat.partial_cmp(&b.beat).unwrap());
+        if let Some(pair) = changes.windows(2).find(|pair| pair[0].beat == pair[1].beat) {
+            bail!(ErrorKind::SpecError(format!(
+                "Tempo changes can't be at the same position: {}",
+                pair[0].beat
+            )));
+        }
+        if changes.is_empty() || changes[0].beat > 0.0 {
+            changes.insert(
+                0,
+                TempoChange {
+                    beat: 0.0,
+                    beats_per_minute,
+                    ramp: false,
+                },
+            );
+        }
+        if let Some(change) = changes.iter().find(|change| change.beat < 0.0) {
+            bail!(ErrorKind::SpecError(format!(
+                "Tempo change can't be before the start of the composition: {}",
+                change.beat
+            )));
+        }
+        if let Some(change) = changes
+            .iter()
+            .find(|change| !change.beats_per_minute.is_finite() || change.beats_per_minute <= 0.0)
+        {
+            bail!(ErrorKind::SpecError(format!(
+                "Tempo must be positive and finite: {}",
+                change.beats_per_minute
+            )));
+        }
+
+        let mut segments: Vec<Segment> = Vec::with_capacity(changes.len());
+        for (i, change) in changes.iter().enumerate() {
+            let start_seconds = match segments.last() {
+                Some(previous) => {
+                    previous.start_seconds
+                        + previous.beats_to_seconds(change.beat - previous.start_beat)
+                }
+                None => 0.0,
+            };
+            let ramp_per_beat = match changes.get(i + 1) {
+                Some(next) if next.ramp && next.beat > change.beat => {
+                    (next.beats_per_minute - change.beats_per_minute) / (next.beat - change.beat)
+                }
+                _ => 0.0,
+            };
+            segments.push(Segment {
+                start_beat: change.beat,
+                start_seconds,
+                beats_per_minute: change.beats_per_minute,
+                ramp_per_beat,
+            });
+        }
+        Ok(TempoMap { segments })
+    }
+
+    /// Create a tempo map that never changes
+    pub fn constant(beats_per_minute: f64) -> TempoMap {
+        TempoMap {
+            segments: vec![Segment {
+                start_beat: 0.0,
+                start_seconds: 0.0,
+                beats_per_minute,
+                ramp_per_beat: 0.0,
+            }],
+        }
+    }
+
+    /// Get how many seconds from the start of the composition a beat is
+    pub fn beats_to_seconds(&self, beats: f64) -> f64 {
+        let segment = self
+            .segments
+            .iter()
+            .rev()
+            .find(|segment| segment.start_beat <= beats)
+            .unwrap_or(&self.segments[0]);
+        segment.start_seconds + segment.beats_to_seconds(beats - segment.start_beat)
+    }
+
+    /// Get how many beats from the start of the composition a second is
+    pub fn seconds_to_beats(&self, seconds: f64) -> f64 {
+        let segment = self
+            .segments
+            .iter()
+            .rev()
+            .find(|segment| segment.start_seconds <= seconds)
+            .unwrap_or(&self.segments[0]);
+        segment.start_beat + segment.seconds_to_beats(seconds - segment.start_seconds)
+    }
+
+    /// Get the slowest tempo in the composition
+    pub fn slowest_beats_per_minute(&self) -> f64 {
+        self.segments
+            .iter()
+            .map(|segment| segment.beats_per_minute)
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+impl FromValue for TempoChange {
+    fn name() -> String {
+        "tempo-change".into()
+    }
+
+    fn from_value(value: Value, consts: &Consts) -> Result<TempoChange> {
+        let mut spec: Spec = value.into_type(consts)?;
+        let at: Time = spec.consume("at", consts)?;
+        if !at.follows_tempo() {
+            bail!(ErrorKind::SpecError(
+                "Tempo changes must be at a time in beats or bars".into()
+            ));
+        }
+        let beat = at.to_beats(consts);
+        let beats_per_minute = spec.consume("beats-per-minute", consts)?;
+        let transition: String = spec.consume_with_default("transition", "step".into(), consts)?;
+        let ramp = match transition.as_str() {
+            "step" => false,
+            "ramp" => true,
+            transition => bail!(ErrorKind::SpecError(format!(
+                "Unrecognized tempo transition: {}",
+                transition
+            ))),
+        };
+        spec.ensure_all_used()?;
+        Ok(TempoChange {
+            beat,
+            beats_per_minute,
+            ramp,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    fn change(beat: f64, beats_per_minute: f64, ramp: bool) -> TempoChange {
+        TempoChange {
+            beat,
+            beats_per_minute,
+            ramp,
+        }
+    }
+
+    #[test]
+    fn test_step() {
+        let tempo = TempoMap::new(120.0, vec![change(4.0, 60.0, false)]).unwrap();
+        assert_close(tempo.beats_to_seconds(2.0), 1.0);
+        assert_close(tempo.beats_to_seconds(4.0), 2.0);
+        assert_close(tempo.beats_to_seconds(6.0), 4.0);
+        assert_close(tempo.seconds_to_beats(4.0), 6.0);
+        assert_close(tempo.slowest_beats_per_minute(), 60.0);
+    }
+
+    #[test]
+    fn test_ramp() {
+        let tempo = TempoMap::new(60.0, vec![change(4.0, 120.0, true)]).unwrap();
+        // Integral of 60 / (60 + 15b) from 0 to 4
+        let ramp_seconds = 4.0 * 2_f64.ln();
+        assert_close(tempo.beats_to_seconds(4.0), ramp_seconds);
+        assert_close(tempo.beats_to_seconds(6.0), ramp_seconds + 1.0);
+        assert_close(tempo.seconds_to_beats(ramp_seconds), 4.0);
+        for beats in &[0.5, 1.0, 3.9, 5.0] {
+            assert_close(
+                tempo.seconds_to_beats(tempo.beats_to_seconds(*beats)),
+                *beats,
+            );
+        }
+        // Slower than the start for the whole ramp
+        assert!(tempo.beats_to_seconds(2.0) > 1.0);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(TempoMap::new(120.0, vec![change(4.0, 0.0, false)]).is_err());
+        assert!(TempoMap::new(120.0, vec![change(-1.0, 60.0, false)]).is_err());
+        assert!(TempoMap::new(120.0, vec![change(f64::NAN, 60.0, false)]).is_err());
+        assert!(TempoMap::new(120.0, vec![change(4.0, f64::NAN, false)]).is_err());
+        assert!(TempoMap::new(f64::INFINITY, vec![change(4.0, 60.0, false)]).is_err());
+        let same_position = vec![change(4.0, 60.0, false), change(4.0, 90.0, true)];
+        assert!(TempoMap::new(120.0, same_position).is_err());
+    }
+}
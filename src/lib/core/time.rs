@@ -1,6 +1,7 @@
 use core::spec::{FromValue, Value};
 use core::Consts;
 use error::*;
+use std::str::FromStr;
 use std::time::Duration;
 
 /// Amount of time in different measurements
@@ -14,10 +15,19 @@ pub enum Time {
     Beats(f64),
     /// Composed of beats
     Bars(f64),
+    /// Position from the start of the composition, e.g. `12:3:240`
+    Position {
+        /// Whole bars from the start, counting from zero
+        bars: usize,
+        /// Beats into the bar, counting from zero
+        beats: f64,
+        /// Ticks after the beat
+        ticks: usize,
+    },
 }
 
 impl Time {
-    #[allow(missing_docs)]
+    /// Get the amount of ticks, measured from the start of the composition
     pub fn to_ticks(&self, consts: &Consts) -> usize {
         match self {
             Time::Ticks(ticks) => *ticks,
@@ -26,22 +36,26 @@ impl Time {
         }
     }
 
-    #[allow(missing_docs)]
+    /// Get the amount of seconds, measured from the start of the composition
     pub fn to_seconds(&self, consts: &Consts) -> f64 {
         match self {
             Time::Seconds(seconds) => *seconds,
             Time::Ticks(ticks) => *ticks as f64 / consts.sample_hz,
-            Time::Beats(beats) => (beats * 60.0) / consts.beats_per_minute,
-            bars => Time::Beats(bars.to_beats(consts)).to_seconds(consts),
+            Time::Beats(beats) => consts.tempo.beats_to_seconds(*beats),
+            Time::Bars(_) => Time::Beats(self.to_beats(consts)).to_seconds(consts),
+            Time::Position { bars, beats, ticks } => {
+                let beats = consts.meter.bars_to_beats(*bars as f64) + beats;
+                consts.tempo.beats_to_seconds(beats) + *ticks as f64 / consts.sample_hz
+            }
         }
     }
 
-    #[allow(missing_docs)]
+    /// Get the amount of beats, measured from the start of the composition
     pub fn to_beats(&self, consts: &Consts) -> f64 {
         match self {
             Time::Beats(beats) => *beats,
-            Time::Bars(bars) => bars * consts.beats_per_bar,
-            Time::Seconds(seconds) => (seconds * consts.beats_per_minute) / 60.0,
+            Time::Bars(bars) => consts.meter.bars_to_beats(*bars),
+            Time::Seconds(seconds) => consts.tempo.seconds_to_beats(*seconds),
             ticks => Time::Seconds(ticks.to_seconds(consts)).to_beats(consts),
         }
     }
@@ -51,6 +65,83 @@ impl Time {
         Duration::from_nanos((self.to_seconds(consts) * 1e9) as u64)
     }
 
+    /// Check if the amount of time is musical, and so changes length with the
+    /// tempo
+    pub fn follows_tempo(&self) -> bool {
+        match self {
+            Time::Beats(_) | Time::Bars(_) | Time::Position { .. } => true,
+            Time::Ticks(_) | Time::Seconds(_) => false,
+        }
+    }
+
+    /// Get how many of this amount of time have passed at `tick`, following
+    /// changes in tempo
+    pub fn count_at(&self, tick: f64, consts: &Consts) -> f64 {
+        let beats = consts.tempo.seconds_to_beats(tick / consts.sample_hz);
+        match self {
+            Time::Bars(bars) => consts.meter.beats_to_bars(beats) / bars,
+            _ if self.follows_tempo() => beats / self.to_beats(consts),
+            _ => tick / (self.to_seconds(consts) * consts.sample_hz),
+        }
+    }
+
+    /// Get how many ticks this amount of time lasts when it ends at `tick`,
+    /// following changes in tempo
+    pub fn ticks_before(&self, tick: usize, consts: &Consts) -> usize {
+        if self.follows_tempo() {
+            let end_beats = consts
+                .tempo
+                .seconds_to_beats(tick as f64 / consts.sample_hz);
+            let start_beats = match self {
+                Time::Bars(bars) => {
+                    let end_bars = consts.meter.beats_to_bars(end_beats);
+                    consts.meter.bars_to_beats((end_bars - bars).max(0.0))
+                }
+                _ => (end_beats - self.to_beats(consts)).max(0.0),
+            };
+            let start_tick = consts.tempo.beats_to_seconds(start_beats) * consts.sample_hz;
+            tick - (start_tick.round() as usize).min(tick)
+        } else {
+            self.to_ticks(consts)
+        }
+    }
+
+    /// Get how many ticks this amount of time lasts when it starts at `tick`,
+    /// following changes in tempo
+    pub fn ticks_after(&self, tick: usize, consts: &Consts) -> usize {
+        if self.follows_tempo() {
+            let start_beats = consts
+                .tempo
+                .seconds_to_beats(tick as f64 / consts.sample_hz);
+            let end_beats = match self {
+                Time::Bars(bars) => {
+                    let start_bars = consts.meter.beats_to_bars(start_beats);
+                    consts.meter.bars_to_beats(start_bars + bars)
+                }
+                _ => start_beats + self.to_beats(consts),
+            };
+            let end_tick = consts.tempo.beats_to_seconds(end_beats) * consts.sample_hz;
+            (end_tick.round() as usize).max(tick) - tick
+        } else {
+            self.to_ticks(consts)
+        }
+    }
+
+    /// Get the most ticks this amount of time lasts anywhere in the
+    /// composition
+    pub fn max_ticks(&self, consts: &Consts) -> usize {
+        if self.follows_tempo() {
+            let beats = match self {
+                Time::Bars(bars) => bars * consts.meter.longest_beats_per_bar(),
+                _ => self.to_beats(consts),
+            };
+            let seconds = beats * 60.0 / consts.tempo.slowest_beats_per_minute();
+            (seconds * consts.sample_hz).ceil() as usize
+        } else {
+            self.to_ticks(consts)
+        }
+    }
+
     /// Represents zero time
     pub fn zero() -> Time {
         Time::Ticks(0)
@@ -63,16 +154,67 @@ impl Time {
             Time::Seconds(seconds) => *seconds == 0.0,
             Time::Beats(beats) => *beats == 0.0,
             Time::Bars(bars) => *bars == 0.0,
+            Time::Position { bars, beats, ticks } => *bars == 0 && *beats == 0.0 && *ticks == 0,
         }
     }
 }
 
-impl FromValue for Time {
-    fn name() -> String {
-        "time".into()
+impl Time {
+    /// Parse a position like `12:3:240`, meaning 240 ticks after the third
+    /// beat of the twelfth bar, where bars and beats count from one
+    fn from_position(string: &str) -> Result<Time> {
+        let parts: Vec<&str> = string.split(':').collect();
+        let (bar, beat, ticks) = match parts.as_slice() {
+            [bar, beat] => (bar, beat, "0"),
+            [bar, beat, ticks] => (bar, beat, *ticks),
+            _ => bail!(ErrorKind::SpecError(format!(
+                "Positions must be bar:beat or bar:beat:tick, got {}",
+                string
+            ))),
+        };
+        let bar: usize = bar.parse().chain_err(|| "Failed to parse position bar")?;
+        let beat: f64 = beat.parse().chain_err(|| "Failed to parse position beat")?;
+        if bar < 1 || beat < 1.0 {
+            bail!(ErrorKind::SpecError(format!(
+                "Bars and beats in positions start from one, got {}",
+                string
+            )));
+        }
+        Ok(Time::Position {
+            bars: bar - 1,
+            beats: beat - 1.0,
+            ticks: ticks
+                .parse()
+                .chain_err(|| "Failed to parse position ticks")?,
+        })
     }
-    fn from_value(value: Value, consts: &Consts) -> Result<Time> {
-        let string: String = value.into_type(consts)?;
+
+    /// Parse a fraction of a whole note like `1/8` into beats, where a beat is
+    /// a quarter note
+    fn beats_from_fraction(string: &str) -> Result<f64> {
+        let parts: Vec<&str> = string.split('/').collect();
+        match parts.as_slice() {
+            [numerator, denominator] => {
+                let numerator: f64 = numerator
+                    .parse()
+                    .chain_err(|| "Failed to parse fraction numerator")?;
+                let denominator: f64 = denominator
+                    .parse()
+                    .chain_err(|| "Failed to parse fraction denominator")?;
+                Ok(4.0 * numerator / denominator)
+            }
+            _ => bail!(ErrorKind::SpecError(format!(
+                "Unrecognized fraction: {}",
+                string
+            ))),
+        }
+    }
+}
+
+impl FromStr for Time {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Time> {
         match string.trim().split(' ').collect::<Vec<_>>().as_slice() {
             [number, "ticks"] => Ok(Time::Ticks(
                 number.parse().chain_err(|| "Failed to parse tick number")?,
@@ -90,14 +232,30 @@ impl FromValue for Time {
             [number, "bars"] => Ok(Time::Bars(
                 number.parse().chain_err(|| "Failed to parse bars number")?,
             )),
+            [position] if position.contains(':') => Time::from_position(position),
+            [fraction] if fraction.contains('/') => {
+                Ok(Time::Beats(Time::beats_from_fraction(fraction)?))
+            }
+            ["dotted", fraction] => Ok(Time::Beats(Time::beats_from_fraction(fraction)? * 1.5)),
             _ => Err(ErrorKind::SpecError(format!("Unrecognized time unit: {}", string)).into()),
         }
     }
 }
 
+impl FromValue for Time {
+    fn name() -> String {
+        "time".into()
+    }
+    fn from_value(value: Value, consts: &Consts) -> Result<Time> {
+        let string: String = value.into_type(consts)?;
+        string.parse()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use core::{MeterChange, MeterMap, TempoChange, TempoMap};
 
     #[test]
     fn test_to_ticks() {
@@ -117,6 +275,67 @@ mod test {
         assert_eq!(Time::Beats(1.0).to_seconds(consts), 0.5);
     }
 
+    #[test]
+    fn test_tempo_changes() {
+        let mut consts = Consts::default().unwrap();
+        consts.tempo = TempoMap::new(
+            120.0,
+            vec![TempoChange {
+                beat: 4.0,
+                beats_per_minute: 60.0,
+                ramp: false,
+            }],
+        )
+        .unwrap();
+        assert_eq!(Time::Beats(6.0).to_seconds(&consts), 4.0);
+        assert_eq!(Time::Seconds(4.0).to_beats(&consts), 6.0);
+        assert_eq!(Time::Beats(1.0).count_at(44100.0 * 3.0, &consts), 5.0);
+        assert_eq!(Time::Beats(1.0).ticks_before(44100, &consts), 22050);
+        assert_eq!(Time::Beats(1.0).ticks_before(44100 * 3, &consts), 44100);
+        assert_eq!(Time::Beats(2.0).ticks_before(44100 * 3, &consts), 66150);
+        assert_eq!(Time::Beats(1.0).max_ticks(&consts), 44100);
+        assert_eq!(Time::Seconds(1.0).count_at(44100.0 * 3.0, &consts), 3.0);
+    }
+
+    #[test]
+    fn test_meter_changes() {
+        let mut consts = Consts::default().unwrap();
+        consts.meter = MeterMap::new(
+            4.0,
+            vec![MeterChange {
+                bar: 2,
+                beats_per_bar: 3.0,
+            }],
+        )
+        .unwrap();
+        assert_eq!(Time::Bars(3.0).to_beats(&consts), 11.0);
+        assert_eq!(Time::Bars(1.0).count_at(44100.0 * 5.5, &consts), 3.0);
+        assert_eq!(Time::Bars(1.0).ticks_before(44100 * 7, &consts), 66150);
+        assert_eq!(Time::Bars(1.0).max_ticks(&consts), 88200);
+        assert_eq!(Time::Bars(1.0).ticks_after(44100 * 2, &consts), 88200);
+        assert_eq!(Time::Bars(1.0).ticks_after(44100 * 4, &consts), 66150);
+        let position: Time = "4:2:100".parse().unwrap();
+        assert_eq!(position.to_ticks(&consts), 44100 * 6 + 100);
+    }
+
+    #[test]
+    fn test_parse() {
+        let consts = &Consts::default().unwrap();
+        let beats = |string: &str| string.parse::<Time>().unwrap().to_beats(consts);
+        assert_eq!(beats("2 beats"), 2.0);
+        assert_eq!(beats("1/8"), 0.5);
+        assert_eq!(beats("3/4"), 3.0);
+        assert_eq!(beats("dotted 1/4"), 1.5);
+        assert_eq!(beats("1:1"), 0.0);
+        assert_eq!(beats("2:3"), 6.0);
+        assert_eq!(beats("12:3:0"), 46.0);
+        assert!("0:1:0".parse::<Time>().is_err());
+        assert!("1:0:0".parse::<Time>().is_err());
+        assert!("1:1:1:1".parse::<Time>().is_err());
+        assert!("1/".parse::<Time>().is_err());
+        assert!("2 bar".parse::<Time>().is_err());
+    }
+
     #[test]
     fn test_to_beats() {
         let consts = &Consts::default().unwrap();
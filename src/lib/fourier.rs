@@ -3,16 +3,16 @@ use rustfft::num_traits::Zero;
 use rustfft::FFTplanner;
 
 /// Performs fourier transform on the input
-pub fn fourier(input: &[i32]) -> Vec<i32> {
+pub fn fourier(input: &[f64]) -> Vec<f64> {
     let mut planner = FFTplanner::new(false);
     let ftt = planner.plan_fft(input.len() as usize);
 
     assert!(!input.is_empty());
-    let input_min = input.iter().min().unwrap();
-    let input_max = input.iter().max().unwrap();
+    let input_min = input.iter().cloned().fold(f64::INFINITY, f64::min);
+    let input_max = input.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
     let mut input = input
         .iter()
-        .map(|x| (x - input_min) as f64 / (input_max - input_min) as f64 * 2.0 - 1.0)
+        .map(|x| (x - input_min) / (input_max - input_min) * 2.0 - 1.0)
         .map(|x| Complex::new(x, 0.0))
         .collect::<Vec<_>>();
     let mut output = vec![Complex::zero(); input.len()];
@@ -21,7 +21,6 @@ pub fn fourier(input: &[i32]) -> Vec<i32> {
     output
         .into_iter()
         .map(|x| (x.re.powf(2.0) + x.im.powf(2.0)).sqrt())
-        .map(|x| x * (input_max - input_min) as f64)
-        .map(|x| x as i32)
+        .map(|x| x * (input_max - input_min))
         .collect()
 }
@@ -16,7 +16,8 @@ pub struct Constant {
 }
 
 impl Constant {
-    fn new(value: f64) -> Constant {
+    #[allow(missing_docs)]
+    pub fn new(value: f64) -> Constant {
         Constant { value }
     }
 }
@@ -73,27 +73,60 @@ impl Function {
     pub fn default() -> Function {
         Self::from_string("sine".into(), false).expect("Failed to create default function")
     }
-}
 
-impl Input for Function {
-    fn get(&mut self, state: &State) -> f64 {
-        let milli_tick = match &self.time_mod {
-            Some(time_mod) => {
-                let time_milli_tick = time_mod.to_ticks(&state.consts) * 1000;
-                let milli_tick_mod = state.milli_tick % time_milli_tick;
-                if self.reversed {
+    /// Get the length of the time mod in milli ticks, if it has a fixed length
+    fn time_milli_tick(&self, consts: &Consts) -> Option<usize> {
+        self.time_mod
+            .as_ref()
+            .filter(|time_mod| !time_mod.follows_tempo())
+            .map(|time_mod| time_mod.to_ticks(consts) * 1000)
+    }
+
+    fn get_at(&self, milli_tick: usize, time_milli_tick: Option<usize>, consts: &Consts) -> f64 {
+        let fn_input = match (time_milli_tick, &self.time_mod) {
+            (Some(time_milli_tick), _) => {
+                let milli_tick_mod = milli_tick % time_milli_tick;
+                let milli_tick = if self.reversed {
                     time_milli_tick - milli_tick_mod
                 } else {
                     milli_tick_mod
-                }
+                };
+                (milli_tick as f64) / 1000.0 / consts.sample_hz
             }
-            None => state.milli_tick,
+            // Time mods that follow the tempo stretch with it, so find how far
+            // through the current time mod we are
+            (None, Some(time_mod)) => {
+                let progress = time_mod
+                    .count_at(milli_tick as f64 / 1000.0, consts)
+                    .fract();
+                let progress = if self.reversed {
+                    1.0 - progress
+                } else {
+                    progress
+                };
+                progress * time_mod.to_seconds(consts)
+            }
+            (None, None) => (milli_tick as f64) / 1000.0 / consts.sample_hz,
         };
-        let fn_input = (milli_tick as f64) / 1000.0 / state.consts.sample_hz;
         (*self.function)(fn_input)
     }
 }
 
+impl Input for Function {
+    fn get(&mut self, state: &State) -> f64 {
+        let time_milli_tick = self.time_milli_tick(&state.consts);
+        self.get_at(state.milli_tick, time_milli_tick, &state.consts)
+    }
+
+    fn get_block(&mut self, state: &State, block: &mut [f64]) {
+        // Only convert the time mod once for the whole block
+        let time_milli_tick = self.time_milli_tick(&state.consts);
+        for (i, value) in block.iter_mut().enumerate() {
+            *value = self.get_at(state.milli_tick + i * 1000, time_milli_tick, &state.consts);
+        }
+    }
+}
+
 impl Tree for Function {
     fn to_tree(&self) -> &dyn Tree {
         self as &dyn Tree
@@ -142,4 +175,16 @@ mod test {
         );
         assert!((0.0 - function.get(&state.with_tick(consts.sample_hz as usize))).abs() < 0.001);
     }
+
+    #[test]
+    fn test_get_block() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let state = State::initial(consts).with_tick(1000);
+        let mut function = Function::from_string("saw".into(), true).unwrap();
+        let mut block = vec![0.0; 100];
+        function.get_block(&state, &mut block);
+        for (i, value) in block.into_iter().enumerate() {
+            assert_eq!(value, function.get(&state.with_tick(1000 + i)));
+        }
+    }
 }
@@ -30,6 +30,13 @@ impl Input for InputMod {
     fn get(&mut self, state: &State) -> f64 {
         self.input.get(state) * self.mult + self.add
     }
+
+    fn get_block(&mut self, state: &State, block: &mut [f64]) {
+        self.input.get_block(state, block);
+        for value in block.iter_mut() {
+            *value = *value * self.mult + self.add;
+        }
+    }
 }
 
 impl Tree for InputMod {
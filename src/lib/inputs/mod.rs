@@ -2,20 +2,20 @@
 
 use core::Input;
 
-mod buffer;
 mod constant;
 mod function;
 mod input_mod;
 mod key;
+mod noise;
 mod random;
 mod smooth_bool;
 mod timeline;
 
-pub use self::buffer::Buffer;
 pub use self::constant::Constant;
 pub use self::function::Function;
 pub use self::input_mod::InputMod;
 pub use self::key::Key;
+pub use self::noise::{Noise, NoiseColor};
 pub use self::random::Random;
 pub use self::smooth_bool::SmoothBool;
 pub use self::timeline::Timeline;
@@ -29,5 +29,6 @@ impl_super_from_value!(
     Timeline,
     InputMod,
     Random,
-    Key
+    Key,
+    Noise
 );
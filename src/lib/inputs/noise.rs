@@ -0,0 +1,222 @@
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::tree::Tree;
+use core::Consts;
+use core::Input;
+use core::State;
+use core::Time;
+use error::*;
+
+use rand;
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+field_decl!(
+    COLOR,
+    String,
+    "Spectrum of the noise, one of white, pink or brown",
+    |_| "white".to_string()
+);
+field_decl!(
+    SEED,
+    Option<i32>,
+    "Seed for the noise, so that it's the same every time it's played. Random if not given",
+    |_| None
+);
+field_decl!(
+    RATE,
+    Time,
+    "How long each value is held for, as a sample and hold. Defaults to a new value every tick",
+    |_| Time::zero()
+);
+
+/// Spectrum of noise
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoiseColor {
+    /// Equal power at all frequencies
+    White,
+    /// Power falls by 3dB per octave
+    Pink,
+    /// Power falls by 6dB per octave
+    Brown,
+}
+
+impl NoiseColor {
+    fn from_string(string: &str) -> Result<NoiseColor> {
+        match string {
+            "white" => Ok(NoiseColor::White),
+            "pink" => Ok(NoiseColor::Pink),
+            "brown" => Ok(NoiseColor::Brown),
+            color => {
+                Err(ErrorKind::SpecError(format!("Unrecognized noise color: {}", color)).into())
+            }
+        }
+    }
+}
+
+/// Supply random input in [-1, 1] with a given spectrum
+pub struct Noise {
+    color: NoiseColor,
+    rng: XorShiftRng,
+    rate: Time,
+    /// Filter state for pink and brown noise
+    filter: [f64; 7],
+    /// The index of the held value, and the held value
+    held: Option<(usize, f64)>,
+}
+
+impl Noise {
+    #[allow(missing_docs)]
+    pub fn new(color: NoiseColor, seed: Option<i32>, rate: Time) -> Noise {
+        let rng = match seed {
+            // XorShift can't be seeded with all zeros
+            Some(seed) => {
+                XorShiftRng::from_seed([seed as u32, 0x193a_6754, 0xa8a7_d469, 0x9783_0e05])
+            }
+            None => rand::weak_rng(),
+        };
+        Noise {
+            color,
+            rng,
+            rate,
+            filter: [0.0; 7],
+            held: None,
+        }
+    }
+
+    fn next_value(&mut self) -> f64 {
+        let white = self.rng.gen_range(-1.0, 1.0);
+        let filter = &mut self.filter;
+        let value = match self.color {
+            NoiseColor::White => white,
+            // Paul Kellett's refined pink noise filter
+            NoiseColor::Pink => {
+                filter[0] = 0.99886 * filter[0] + white * 0.055_517_9;
+                filter[1] = 0.99332 * filter[1] + white * 0.075_075_9;
+                filter[2] = 0.96900 * filter[2] + white * 0.153_852_0;
+                filter[3] = 0.86650 * filter[3] + white * 0.310_485_6;
+                filter[4] = 0.55000 * filter[4] + white * 0.532_952_2;
+                filter[5] = -0.7616 * filter[5] - white * 0.016_898_0;
+                let pink = filter[..6].iter().sum::<f64>() + filter[6] + white * 0.5362;
+                filter[6] = white * 0.115_926;
+                pink * 0.11
+            }
+            // Leaky integration of white noise
+            NoiseColor::Brown => {
+                filter[0] = (filter[0] + 0.02 * white) / 1.02;
+                filter[0] * 3.5
+            }
+        };
+        value.clamp(-1.0, 1.0)
+    }
+}
+
+impl Input for Noise {
+    fn get(&mut self, state: &State) -> f64 {
+        let index = if self.rate.is_zero() {
+            state.tick()
+        } else {
+            self.rate
+                .count_at(state.milli_tick as f64 / 1000.0, &state.consts)
+                .floor() as usize
+        };
+        match self.held {
+            Some((held_index, value)) if held_index == index => value,
+            _ => {
+                let value = self.next_value();
+                self.held = Some((index, value));
+                value
+            }
+        }
+    }
+}
+
+impl Tree for Noise {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+}
+
+impl SpecType for Noise {
+    fn name() -> String {
+        "noise".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![
+            COLOR.to_description(),
+            SEED.to_description(),
+            RATE.to_description(),
+        ]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Noise> {
+        let color = NoiseColor::from_string(&COLOR.get(&mut spec, consts)?)?;
+        let seed = SEED.get(&mut spec, consts)?;
+        let rate = RATE.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        Ok(Noise::new(color, seed, rate))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::Arc;
+
+    fn get(noise: &mut Noise, num_ticks: usize) -> Vec<f64> {
+        let mut state = State::initial(Arc::new(Consts::default().unwrap()));
+        (0..num_ticks)
+            .map(|_| {
+                let value = noise.get(&state);
+                state.increment();
+                value
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_seed() {
+        for color in &[NoiseColor::White, NoiseColor::Pink, NoiseColor::Brown] {
+            let values = get(&mut Noise::new(*color, Some(5), Time::zero()), 1000);
+            assert_eq!(
+                values,
+                get(&mut Noise::new(*color, Some(5), Time::zero()), 1000)
+            );
+            assert_ne!(
+                values,
+                get(&mut Noise::new(*color, Some(6), Time::zero()), 1000)
+            );
+            assert!(values.iter().all(|value| value.abs() <= 1.0));
+        }
+    }
+
+    #[test]
+    fn test_color() {
+        // Redder noise changes less between ticks
+        let mean_change = |color| {
+            let values = get(&mut Noise::new(color, Some(1), Time::zero()), 10000);
+            values
+                .windows(2)
+                .map(|pair| (pair[1] - pair[0]).abs())
+                .sum::<f64>()
+                / values.len() as f64
+        };
+        let white = mean_change(NoiseColor::White);
+        let pink = mean_change(NoiseColor::Pink);
+        let brown = mean_change(NoiseColor::Brown);
+        assert!(white > pink && pink > brown);
+    }
+
+    #[test]
+    fn test_rate() {
+        let values = get(&mut Noise::new(NoiseColor::White, None, Time::Ticks(4)), 12);
+        for chunk in values.chunks(4) {
+            assert!(chunk.iter().all(|value| *value == chunk[0]));
+        }
+        assert!(values[0] != values[4] && values[4] != values[8]);
+        assert!(NoiseColor::from_string("blue").is_err());
+    }
+}
@@ -12,44 +12,134 @@ use error::*;
 field_decl!(
     EVENTS,
     String,
-    "The events, where a space means false and other characters mean true"
+    "The events, where _ is off, 0-9 are levels, x is soft, X is accented, - \
+     holds the previous event, and other characters are on"
 );
 field_decl!(EVENT_DURATION, Time, "The duration of an event");
+field_decl!(
+    RETRIGGER,
+    bool,
+    "Whether consecutive events that are on are separated by a tick of \
+     silence, so that they retrigger. Held events are never separated",
+    |_| false
+);
+
+/// Level of soft events, written as `x`
+const SOFT_LEVEL: f64 = 0.5;
+
+#[derive(Clone, Copy)]
+struct Event {
+    level: f64,
+    /// If the event holds the previous event, rather than starting a new one
+    held: bool,
+}
 
-/// `input::Bool` defined by a list of booleans
+/// `Input` defined by a list of events
+///
+/// When retriggering, consecutive events that are both on are separated by a
+/// single tick of silence, so that players triggered by the input are
+/// triggered again. Held events continue the previous event without a gap.
 pub struct Timeline {
-    /// Events activation, indexed by `[time step][category]`
-    events: Vec<bool>,
+    /// Events, indexed by time step
+    events: Vec<Event>,
     event_duration: Time,
+    retrigger: bool,
 }
 
 impl Timeline {
     #[allow(missing_docs)]
     pub fn bool(events: Vec<bool>, event_duration: Time) -> Timeline {
+        let held = |i: usize| events[i] && i > 0 && events[i - 1];
         Timeline {
-            events,
+            events: (0..events.len())
+                .map(|i| Event {
+                    level: if events[i] { 1.0 } else { 0.0 },
+                    held: held(i),
+                })
+                .collect(),
             event_duration,
+            retrigger: false,
         }
     }
 
     #[allow(missing_docs)]
-    pub fn from_string(events_str: String, event_duration: Time) -> Timeline {
+    pub fn from_string(events_str: String, event_duration: Time, retrigger: bool) -> Timeline {
+        let mut events: Vec<Event> = events_str
+            .chars()
+            .map(|c| Event {
+                level: match c {
+                    '_' | '-' => 0.0,
+                    'x' => SOFT_LEVEL,
+                    c => c.to_digit(10).map_or(1.0, |digit| f64::from(digit) / 9.0),
+                },
+                held: c == '-',
+            })
+            .collect();
+        // Held events take the level of the event they hold, which wraps
+        // around to the end of the timeline for leading holds
+        if let Some(start) = events.iter().position(|event| !event.held) {
+            for i in (start + 1..events.len()).chain(0..start) {
+                if events[i].held {
+                    let previous = if i == 0 { events.len() - 1 } else { i - 1 };
+                    events[i].level = events[previous].level;
+                }
+            }
+        }
         Timeline {
-            events: events_str.chars().map(|c| c != '_').collect(),
+            events,
             event_duration,
+            retrigger,
         }
     }
+
+    /// Split a grid of events into one row of events per line, checking that
+    /// every row is the same length
+    pub fn split_grid(events_str: &str) -> Result<Vec<String>> {
+        let rows: Vec<String> = events_str
+            .lines()
+            .map(|row| row.trim().to_string())
+            .filter(|row| !row.is_empty())
+            .collect();
+        let row_length = match rows.first() {
+            Some(row) => row.chars().count(),
+            None => bail!(ErrorKind::SpecError("Timeline grid has no rows".into())),
+        };
+        for (i, row) in rows.iter().enumerate() {
+            if row.chars().count() != row_length {
+                bail!(ErrorKind::SpecError(format!(
+                    "Row {} of timeline grid has {} events, but row 1 has {}: {}",
+                    i + 1,
+                    row.chars().count(),
+                    row_length,
+                    row
+                )));
+            }
+        }
+        Ok(rows)
+    }
 }
 
 impl Input for Timeline {
     fn get(&mut self, state: &State) -> f64 {
-        let event_index = state.tick() as usize
-            % (self.events.len() * self.event_duration.to_ticks(&state.consts))
-            / (self.event_duration.to_ticks(&state.consts));
-        if self.events[event_index] {
-            1.0
-        } else {
+        let tick = state.tick();
+        let position = self
+            .event_duration
+            .count_at(tick as f64, &state.consts)
+            .floor();
+        let event_index = position as usize % self.events.len();
+        let event = self.events[event_index];
+        let previous_index = event_index.checked_sub(1).unwrap_or(self.events.len() - 1);
+        let previous_on = self.events[previous_index].level > 0.0;
+        let event_start = tick == 0
+            || self
+                .event_duration
+                .count_at((tick - 1) as f64, &state.consts)
+                .floor()
+                < position;
+        if self.retrigger && event_start && !event.held && previous_on {
             0.0
+        } else {
+            event.level
         }
     }
 }
@@ -66,13 +156,111 @@ impl SpecType for Timeline {
     }
 
     fn field_descriptions() -> Vec<SpecFieldDescription> {
-        vec![EVENTS.to_description(), EVENT_DURATION.to_description()]
+        vec![
+            EVENTS.to_description(),
+            EVENT_DURATION.to_description(),
+            RETRIGGER.to_description(),
+        ]
     }
 
     fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Self> {
         let event_duration = EVENT_DURATION.get(&mut spec, consts)?;
         let events = EVENTS.get(&mut spec, consts)?;
+        let retrigger = RETRIGGER.get(&mut spec, consts)?;
         spec.ensure_all_used()?;
-        Ok(Timeline::from_string(events, event_duration))
+        Ok(Timeline::from_string(events, event_duration, retrigger))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::{TempoChange, TempoMap};
+
+    use std::sync::Arc;
+
+    fn levels(events: &str, retrigger: bool) -> Vec<f64> {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut timeline = Timeline::from_string(events.into(), Time::Ticks(10), retrigger);
+        let mut levels = vec![0.0; events.len() * 10];
+        timeline.get_block(&State::initial(consts), &mut levels);
+        levels
+    }
+
+    #[test]
+    fn test_levels() {
+        let levels = levels("_x9X3a0", false);
+        assert_eq!(levels[5], 0.0);
+        assert_eq!(levels[15], 0.5);
+        assert_eq!(levels[25], 1.0);
+        assert_eq!(levels[35], 1.0);
+        assert_eq!(levels[45], 3.0 / 9.0);
+        assert_eq!(levels[55], 1.0);
+        assert_eq!(levels[65], 0.0);
+    }
+
+    #[test]
+    fn test_holds() {
+        let levels = levels("-5_X-X", true);
+        // Leading holds wrap around to the last event
+        assert_eq!(levels[0], 1.0);
+        // New events after another event are retriggered
+        assert_eq!(levels[10], 0.0);
+        assert_eq!(levels[11], 5.0 / 9.0);
+        assert_eq!(levels[15], 5.0 / 9.0);
+        assert_eq!(levels[20], 0.0);
+        // No gap needed after an off event
+        assert_eq!(levels[30], 1.0);
+        // Held events aren't retriggered
+        assert_eq!(levels[40], 1.0);
+        assert_eq!(levels[50], 0.0);
+        assert_eq!(levels[51], 1.0);
+    }
+
+    #[test]
+    fn test_retrigger() {
+        let state = State::initial(Arc::new(Consts::default().unwrap()));
+        let loop_start = |retrigger| {
+            Timeline::from_string("X9".into(), Time::Ticks(10), retrigger).get(&state.with_tick(20))
+        };
+        // Without retriggering, the level is continuous between consecutive
+        // events and when looping back to the start
+        assert!(levels("X9", false).iter().all(|level| *level == 1.0));
+        assert_eq!(loop_start(false), 1.0);
+
+        let retriggered = levels("X9", true);
+        assert_eq!(retriggered[10], 0.0);
+        assert_eq!(retriggered[11], 1.0);
+        assert_eq!(loop_start(true), 0.0);
+    }
+
+    #[test]
+    fn test_tempo_changes() {
+        let mut consts = Consts::default().unwrap();
+        // Half speed after the first two beats
+        consts.tempo = TempoMap::new(
+            120.0,
+            vec![TempoChange {
+                beat: 2.0,
+                beats_per_minute: 60.0,
+                ramp: false,
+            }],
+        )
+        .unwrap();
+        let mut timeline = Timeline::from_string("X_".into(), Time::Beats(1.0), false);
+        let state = State::initial(Arc::new(consts));
+        assert_eq!(timeline.get(&state.with_tick(11025)), 1.0);
+        assert_eq!(timeline.get(&state.with_tick(33075)), 0.0);
+        assert_eq!(timeline.get(&state.with_tick(44100 + 33075)), 1.0);
+        assert_eq!(timeline.get(&state.with_tick(44100 * 2 + 11025)), 0.0);
+    }
+
+    #[test]
+    fn test_split_grid() {
+        let rows = Timeline::split_grid("\n  X__X\n  _X_X\n\n  __XX\n").unwrap();
+        assert_eq!(rows, vec!["X__X", "_X_X", "__XX"]);
+        let error = Timeline::split_grid("X__X\n_X_\n__XX").unwrap_err();
+        assert!(error.to_string().contains("Row 2"));
+        assert!(Timeline::split_grid("\n").is_err());
     }
 }
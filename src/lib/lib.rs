@@ -13,6 +13,8 @@
 
 /// Default configuration file path
 pub const DEFAULT_CONFIG_PATH: &str = "./composer.config";
+/// Default composition specification path
+pub const DEFAULT_SPEC_PATH: &str = "./composition.yaml";
 
 #[macro_use]
 extern crate error_chain;
@@ -1,9 +1,23 @@
 //! Implementations of [`Output`](../core/trait.Output.html)
 
 use core::Output;
+use core::NUM_CHANNELS;
 
 mod speaker;
+mod wav_file;
 
 pub use self::speaker::Speaker;
+pub use self::wav_file::WavFile;
 
-impl_super_from_value!(dyn Output, "output", Speaker);
+impl_super_from_value!(dyn Output, "output", Speaker, WavFile);
+
+/// Check that an output can write `channels` channels
+fn num_channels(channels: i32) -> Result<usize> {
+    if channels < 1 || channels as usize > NUM_CHANNELS {
+        bail!(ErrorKind::SpecError(format!(
+            "Number of channels must be between 1 and {}, got {}",
+            NUM_CHANNELS, channels
+        )));
+    }
+    Ok(channels as usize)
+}
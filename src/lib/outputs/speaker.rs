@@ -1,6 +1,7 @@
 //! Play music to the device speaker
 
 use core::spec::Spec;
+use core::spec::SpecField;
 use core::spec::SpecFieldDescription;
 use core::spec::SpecType;
 use core::Output;
@@ -13,11 +14,17 @@ use std::sync::{mpsc, Arc, Mutex};
 use core::Consts;
 use portaudio;
 
-const NUM_CHANNELS: i32 = 1;
 const FRAMES: usize = 32;
 const MAX_UNPLAYED_BUFFERS: usize = 2;
 
-type Stream = portaudio::stream::Stream<portaudio::stream::NonBlocking, portaudio::Output<i32>>;
+field_decl!(
+    CHANNELS,
+    i32,
+    "Number of channels to play, mono output plays a mix of all channels",
+    |_| 1
+);
+
+type Stream = portaudio::stream::Stream<portaudio::stream::NonBlocking, portaudio::Output<f32>>;
 
 /// Play music to a device speaker
 pub struct Speaker {
@@ -36,20 +43,20 @@ pub struct Speaker {
 
 impl Speaker {
     #[allow(missing_docs)]
-    fn new(output_frequency: f64) -> Result<Self> {
+    fn new(output_frequency: f64, num_channels: usize) -> Result<Self> {
         // Initialize portaudio interface
         let audio = portaudio::PortAudio::new().chain_err(|| "Failed to initialize PortAudio")?;
 
         // Create the stream settings
         let mut audio_settings = audio
-            .default_output_stream_settings(NUM_CHANNELS, output_frequency, FRAMES as u32)
+            .default_output_stream_settings(num_channels as i32, output_frequency, FRAMES as u32)
             .chain_err(|| "Failed to get default audio stream settings")?;
         audio_settings.flags = portaudio::stream_flags::CLIP_OFF;
 
         // Create and start the stream
         let audio_buffers = Arc::new(Mutex::new(VecDeque::new()));
         let (write_sender, write_reciever) = mpsc::channel();
-        let callback = Self::create_callback(audio_buffers.clone(), write_sender);
+        let callback = Self::create_callback(audio_buffers.clone(), write_sender, num_channels);
         let mut audio_stream = audio
             .open_non_blocking_stream(audio_settings, callback)
             .chain_err(|| "Failed to open audio stream")?;
@@ -63,7 +70,7 @@ impl Speaker {
             audio_buffers
                 .lock()
                 .unwrap()
-                .push_back([Playable::new(0); FRAMES]);
+                .push_back([Playable::zero(); FRAMES]);
         }
         for _ in 0..32 {
             write_reciever.recv().unwrap();
@@ -73,7 +80,7 @@ impl Speaker {
             audio_stream,
             write_reciever,
             audio_buffers,
-            current_buffer: [Playable::new(0); FRAMES],
+            current_buffer: [Playable::zero(); FRAMES],
             current_buffer_index: 0,
         })
     }
@@ -81,9 +88,10 @@ impl Speaker {
     fn create_callback(
         audio_buffers: Arc<Mutex<VecDeque<[Playable; FRAMES]>>>,
         write_sender: mpsc::Sender<()>,
-    ) -> impl Fn(portaudio::OutputStreamCallbackArgs<'static, i32>) -> portaudio::stream::CallbackResult
+        num_channels: usize,
+    ) -> impl Fn(portaudio::OutputStreamCallbackArgs<'static, f32>) -> portaudio::stream::CallbackResult
     {
-        move |args: portaudio::OutputStreamCallbackArgs<'static, i32>| {
+        move |args: portaudio::OutputStreamCallbackArgs<'static, f32>| {
             let buffer = audio_buffers.lock().unwrap().pop_front();
             if buffer.is_none() {
                 warn!("Playables aren't produced fast enough to write");
@@ -91,8 +99,12 @@ impl Speaker {
             }
 
             let playables = buffer.unwrap();
-            for (buffer_value, playable) in args.buffer.iter_mut().zip(playables.iter()) {
-                *buffer_value = playable.get_value();
+            for (frame, playable) in args.buffer.chunks_mut(num_channels).zip(playables.iter()) {
+                for (channel, buffer_value) in frame.iter_mut().enumerate() {
+                    *buffer_value = playable
+                        .get_output_value(channel, num_channels)
+                        .clamp(-1.0, 1.0) as f32;
+                }
             }
 
             write_sender
@@ -133,12 +145,13 @@ impl SpecType for Speaker {
     }
 
     fn field_descriptions() -> Vec<SpecFieldDescription> {
-        Vec::new()
+        vec![CHANNELS.to_description()]
     }
 
-    fn from_spec(spec: Spec, consts: &Consts) -> Result<Self> {
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Self> {
+        let channels = CHANNELS.get(&mut spec, consts)?;
         spec.ensure_all_used()?;
-        Speaker::new(consts.sample_hz)
+        Speaker::new(consts.sample_hz, super::num_channels(channels)?)
     }
 }
 
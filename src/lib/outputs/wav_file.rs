@@ -0,0 +1,206 @@
+//! Write music to a .wav file
+
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::Consts;
+use core::Output;
+use core::Playable;
+use error::*;
+
+use std::fs::File;
+use std::io::BufWriter;
+
+use hound;
+use rand;
+use rand::{Rng, XorShiftRng};
+
+field_decl!(PATH, String, "Path of the .wav file to write to");
+field_decl!(
+    BITS_PER_SAMPLE,
+    i32,
+    "Bit depth of the samples written, one of 8, 16, 24 or 32",
+    |_| 16
+);
+field_decl!(
+    CHANNELS,
+    i32,
+    "Number of channels to write, mono files contain a mix of all channels",
+    |_| 1
+);
+field_decl!(
+    DITHER,
+    bool,
+    "Whether to add triangular dither when reducing to the bit depth",
+    |_| false
+);
+
+type Writer = hound::WavWriter<BufWriter<File>>;
+
+/// Write music to a .wav file
+pub struct WavFile {
+    /// Only `None` once the writer has been finalized on drop
+    writer: Option<Writer>,
+    /// The largest integer value that can be written
+    max_value: f64,
+    num_channels: usize,
+    /// Only `Some` if dither is enabled
+    dither_rng: Option<XorShiftRng>,
+}
+
+impl WavFile {
+    #[allow(missing_docs)]
+    pub fn new(
+        path: String,
+        bits_per_sample: i32,
+        num_channels: usize,
+        dither: bool,
+        consts: &Consts,
+    ) -> Result<WavFile> {
+        let bits_per_sample = match bits_per_sample {
+            8 | 16 | 24 | 32 => bits_per_sample as u16,
+            bits => bail!(ErrorKind::SpecError(format!(
+                "Unsupported bits per sample: {}",
+                bits
+            ))),
+        };
+        let spec = hound::WavSpec {
+            channels: num_channels as u16,
+            sample_rate: consts.sample_hz as u32,
+            bits_per_sample,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let writer = hound::WavWriter::create(&path, spec)
+            .chain_err(|| format!("Failed to create .wav file: {}", path))?;
+        Ok(WavFile {
+            writer: Some(writer),
+            max_value: f64::from((1u32 << (bits_per_sample - 1)) - 1),
+            num_channels,
+            dither_rng: if dither { Some(rand::weak_rng()) } else { None },
+        })
+    }
+
+    /// Convert a value in [-1, 1] to an integer in the bit depth
+    fn quantize(&mut self, value: f64) -> i32 {
+        let mut scaled = value.clamp(-1.0, 1.0) * self.max_value;
+        if let Some(rng) = &mut self.dither_rng {
+            scaled += rng.gen_range(0.0, 1.0) - rng.gen_range(0.0, 1.0);
+        }
+        scaled.round().clamp(-self.max_value - 1.0, self.max_value) as i32
+    }
+}
+
+impl Output for WavFile {
+    fn write(&mut self, playable: Playable) {
+        for channel in 0..self.num_channels {
+            let value = self.quantize(playable.get_output_value(channel, self.num_channels));
+            self.writer
+                .as_mut()
+                .expect("Writing to finalized .wav file")
+                .write_sample(value)
+                .expect("Failed to write to .wav file");
+        }
+    }
+}
+
+impl SpecType for WavFile {
+    fn name() -> String {
+        "wav-file".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![
+            PATH.to_description(),
+            BITS_PER_SAMPLE.to_description(),
+            CHANNELS.to_description(),
+            DITHER.to_description(),
+        ]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Self> {
+        let path = PATH.get(&mut spec, consts)?;
+        let bits_per_sample = BITS_PER_SAMPLE.get(&mut spec, consts)?;
+        let channels = CHANNELS.get(&mut spec, consts)?;
+        let dither = DITHER.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        WavFile::new(
+            path,
+            bits_per_sample,
+            super::num_channels(channels)?,
+            dither,
+            consts,
+        )
+    }
+}
+
+impl Drop for WavFile {
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            writer.finalize().expect("Failed to finalize .wav file");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::env;
+
+    #[test]
+    fn test_write_and_read() {
+        let path = env::temp_dir().join("composer_test_wav_file.wav");
+        let path_str = path.to_str().unwrap().to_string();
+        let consts = Consts::default().unwrap();
+        let values = vec![0.0, 1.0, -1.0, 2.0, -2.0, 0.5, -0.5];
+        {
+            let mut wav_file = WavFile::new(path_str.clone(), 16, 1, false, &consts).unwrap();
+            for value in &values {
+                wav_file.write(Playable::new(*value));
+            }
+        }
+
+        let mut reader = hound::WavReader::open(&path_str).unwrap();
+        assert_eq!(reader.spec().sample_rate, 44100);
+        assert_eq!(reader.spec().bits_per_sample, 16);
+        let read: Vec<i32> = reader.samples::<i32>().map(|s| s.unwrap()).collect();
+        assert_eq!(read, vec![0, 32767, -32767, 32767, -32767, 16384, -16384]);
+    }
+
+    #[test]
+    fn test_write_stereo() {
+        let path = env::temp_dir().join("composer_test_wav_file_stereo.wav");
+        let path_str = path.to_str().unwrap().to_string();
+        let consts = Consts::default().unwrap();
+        {
+            let mut wav_file = WavFile::new(path_str.clone(), 16, 2, false, &consts).unwrap();
+            wav_file.write(Playable::stereo(0.25, -0.5));
+        }
+
+        let mut reader = hound::WavReader::open(&path_str).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        let read: Vec<i32> = reader.samples::<i32>().map(|s| s.unwrap()).collect();
+        assert_eq!(read, vec![8192, -16384]);
+    }
+
+    #[test]
+    fn test_dither() {
+        let path = env::temp_dir().join("composer_test_wav_file_dither.wav");
+        let consts = Consts::default().unwrap();
+        let mut wav_file =
+            WavFile::new(path.to_str().unwrap().into(), 8, 1, true, &consts).unwrap();
+        let values: Vec<i32> = (0..1000).map(|_| wav_file.quantize(0.1)).collect();
+        // 0.1 * 127 = 12.7, so dither should round to either side
+        assert!(values.iter().all(|v| *v >= 11 && *v <= 14));
+        assert!(values.contains(&12));
+        assert!(values.contains(&13));
+    }
+
+    #[test]
+    fn test_bad_bits_per_sample() {
+        let path = env::temp_dir().join("composer_test_wav_file_bad.wav");
+        let consts = Consts::default().unwrap();
+        assert!(WavFile::new(path.to_str().unwrap().into(), 12, 1, false, &consts).is_err());
+    }
+}
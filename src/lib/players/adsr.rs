@@ -0,0 +1,322 @@
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::tree::Tree;
+use core::Consts;
+use core::Input;
+use core::Playable;
+use core::Player;
+use core::State;
+use core::Time;
+use error::*;
+use inputs::Function;
+
+field_decl!(CHILD, Box<dyn Player>, "Child to apply the envelope to");
+field_decl!(
+    INPUT,
+    Box<dyn Input>,
+    "Gate of the envelope, attacks when turned on and releases when turned off"
+);
+field_decl!(ATTACK, Time, "How long to rise to full volume", |_| {
+    Time::Seconds(0.01)
+});
+field_decl!(DECAY, Time, "How long to fall to the sustain level", |_| {
+    Time::Seconds(0.1)
+});
+field_decl!(
+    SUSTAIN,
+    f64,
+    "Volume held while the gate is on, after the decay",
+    |_| 0.7
+);
+field_decl!(
+    RELEASE,
+    Time,
+    "How long to fall to silence after the gate is off",
+    |_| Time::Seconds(0.2)
+);
+field_decl!(
+    RETRIGGER,
+    bool,
+    "Whether to attack from silence, rather than the current volume, when the \
+     gate turns on",
+    |_| false
+);
+field_decl!(
+    CURVE,
+    Box<dyn Input>,
+    "Shape of each stage, mapping progress in [0, 1] to [0, 1]",
+    |_| Box::new(Function::new(Box::new(|x| x))) as Box<dyn Input>
+);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Shape the volume of a child with an attack, decay, sustain and release
+/// envelope
+pub struct Adsr {
+    child: Box<dyn Player>,
+    gate: Box<dyn Input>,
+    curve: Box<dyn Input>,
+    attack: Time,
+    decay: Time,
+    sustain: f64,
+    release: Time,
+    retrigger: bool,
+    stage: Stage,
+    /// How many ticks we've been in the current stage
+    stage_ticks: usize,
+    /// How many ticks the current stage lasts, found when it starts so that it
+    /// follows the tempo
+    stage_length: usize,
+    /// The volume when the current stage started
+    stage_start_level: f64,
+    level: f64,
+    gate_on: bool,
+}
+
+impl Adsr {
+    #[allow(missing_docs)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn player(
+        child: Box<dyn Player>,
+        gate: Box<dyn Input>,
+        curve: Box<dyn Input>,
+        attack: Time,
+        decay: Time,
+        sustain: f64,
+        release: Time,
+        retrigger: bool,
+    ) -> Adsr {
+        Adsr {
+            child,
+            gate,
+            curve,
+            attack,
+            decay,
+            sustain,
+            release,
+            retrigger,
+            stage: Stage::Idle,
+            stage_ticks: 0,
+            stage_length: 0,
+            stage_start_level: 0.0,
+            level: 0.0,
+            gate_on: false,
+        }
+    }
+
+    fn start_stage(&mut self, stage: Stage, state: &State) {
+        let length = match stage {
+            Stage::Attack => Some(&self.attack),
+            Stage::Decay => Some(&self.decay),
+            Stage::Release => Some(&self.release),
+            Stage::Idle | Stage::Sustain => None,
+        };
+        self.stage_length =
+            length.map_or(0, |length| length.ticks_after(state.tick(), &state.consts));
+        self.stage = stage;
+        self.stage_ticks = 0;
+        self.stage_start_level = self.level;
+    }
+
+    /// Get how far through the current stage we are, and the shaped progress
+    fn progress(&mut self, state: &State) -> (f64, f64) {
+        if self.stage_ticks >= self.stage_length {
+            // Don't pass the end to the curve, as periodic functions wrap to
+            // their start
+            return (1.0, 1.0);
+        }
+        let progress = self.stage_ticks as f64 / self.stage_length as f64;
+        let curve_tick = Time::Seconds(progress).to_ticks(&state.consts);
+        (progress, self.curve.get(&state.with_tick(curve_tick)))
+    }
+
+    fn update_level(&mut self, state: &State) {
+        let gate_on = self.gate.get_bool(state);
+        if gate_on && !self.gate_on {
+            if self.retrigger {
+                self.level = 0.0;
+            }
+            self.start_stage(Stage::Attack, state);
+        } else if !gate_on && self.gate_on {
+            self.start_stage(Stage::Release, state);
+        }
+        self.gate_on = gate_on;
+
+        match self.stage {
+            Stage::Idle => self.level = 0.0,
+            Stage::Attack => {
+                let (progress, shaped) = self.progress(state);
+                self.level = self.stage_start_level + (1.0 - self.stage_start_level) * shaped;
+                if progress >= 1.0 {
+                    self.start_stage(Stage::Decay, state);
+                }
+            }
+            Stage::Decay => {
+                let (progress, shaped) = self.progress(state);
+                self.level = 1.0 - (1.0 - self.sustain) * shaped;
+                if progress >= 1.0 {
+                    self.start_stage(Stage::Sustain, state);
+                }
+            }
+            Stage::Sustain => self.level = self.sustain,
+            Stage::Release => {
+                let (progress, shaped) = self.progress(state);
+                self.level = self.stage_start_level * (1.0 - shaped);
+                if progress >= 1.0 {
+                    self.start_stage(Stage::Idle, state);
+                }
+            }
+        }
+        self.stage_ticks += 1;
+    }
+}
+
+impl Player for Adsr {
+    fn play(&mut self, state: &State) -> Playable {
+        self.update_level(state);
+        self.child.play(state) * self.level
+    }
+}
+
+impl Tree for Adsr {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![
+            self.child.to_tree(),
+            self.gate.to_tree(),
+            self.curve.to_tree(),
+        ]
+    }
+}
+
+impl SpecType for Adsr {
+    fn name() -> String {
+        "adsr".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![
+            CHILD.to_description(),
+            INPUT.to_description(),
+            ATTACK.to_description(),
+            DECAY.to_description(),
+            SUSTAIN.to_description(),
+            RELEASE.to_description(),
+            RETRIGGER.to_description(),
+            CURVE.to_description(),
+        ]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Self> {
+        let child = CHILD.get(&mut spec, consts)?;
+        let gate = INPUT.get(&mut spec, consts)?;
+        let attack = ATTACK.get(&mut spec, consts)?;
+        let decay = DECAY.get(&mut spec, consts)?;
+        let sustain = SUSTAIN.get(&mut spec, consts)?;
+        let release = RELEASE.get(&mut spec, consts)?;
+        let retrigger = RETRIGGER.get(&mut spec, consts)?;
+        let curve = CURVE.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        Ok(Adsr::player(
+            child, gate, curve, attack, decay, sustain, release, retrigger,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::{TempoChange, TempoMap};
+    use inputs::Timeline;
+    use players::test_util::{play, Ones};
+
+    use std::sync::Arc;
+
+    fn levels(retrigger: bool, events: &str, release_ticks: usize) -> Vec<f64> {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut adsr = Adsr::player(
+            Box::new(Ones),
+            Box::new(Timeline::from_string(
+                events.into(),
+                Time::Ticks(100),
+                false,
+            )),
+            Box::new(Function::new(Box::new(|x| x))),
+            Time::Ticks(10),
+            Time::Ticks(10),
+            0.5,
+            Time::Ticks(release_ticks),
+            retrigger,
+        );
+        play(&mut adsr, consts, 400)
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 0.01, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_stages() {
+        let levels = levels(false, "XX__", 20);
+        assert_close(levels[0], 0.0);
+        assert_close(levels[5], 0.5);
+        assert_close(levels[10], 1.0);
+        assert_close(levels[15], 0.75);
+        assert_close(levels[20], 0.5);
+        assert_close(levels[199], 0.5);
+        assert_close(levels[210], 0.25);
+        assert_close(levels[220], 0.0);
+        assert_close(levels[300], 0.0);
+    }
+
+    #[test]
+    fn test_retrigger() {
+        // Gate turns off and on again while still releasing
+        let continued = levels(false, "X_X_", 200);
+        let retriggered = levels(true, "X_X_", 200);
+        assert!(continued[200] > 0.0);
+        assert_close(retriggered[200], 0.0);
+    }
+
+    #[test]
+    fn test_tempo_changes() {
+        // The tempo halves when the gate turns off after the first beat
+        let mut consts = Consts::default().unwrap();
+        consts.tempo = TempoMap::new(
+            120.0,
+            vec![TempoChange {
+                beat: 1.0,
+                beats_per_minute: 60.0,
+                ramp: false,
+            }],
+        )
+        .unwrap();
+        let mut adsr = Adsr::player(
+            Box::new(Ones),
+            Box::new(Timeline::from_string("X_".into(), Time::Beats(1.0), false)),
+            Box::new(Function::new(Box::new(|x| x))),
+            Time::Ticks(10),
+            Time::Ticks(10),
+            0.5,
+            Time::Beats(1.0),
+            false,
+        );
+        let levels = play(&mut adsr, Arc::new(consts), 22050 * 4);
+        // The release lasts a beat at the new tempo
+        assert_close(levels[22050], 0.5);
+        assert_close(levels[22050 * 2], 0.25);
+        assert_close(levels[22050 * 3], 0.0);
+    }
+}
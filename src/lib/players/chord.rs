@@ -0,0 +1,100 @@
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::spec::Value;
+use core::Chord as CoreChord;
+use core::Consts;
+use core::Player;
+use error::*;
+use inputs::Constant;
+use players::Combiner;
+use players::Wave;
+use players::WaveShape;
+
+field_decl!(
+    FN,
+    Value,
+    "The function that defines the wave shape, or a band-limited oscillator, created once for \
+     every voice",
+    |_| Value::Spec(Spec::empty().with("name".into(), "function".to_string()))
+);
+field_decl!(
+    CHORD,
+    CoreChord,
+    "Chord to play, e.g. \"a4 maj\" or \"c4 min7 inversion 1 octave 3\""
+);
+
+/// Play a wave for every note in a chord
+pub struct Chord {}
+
+impl Chord {
+    #[allow(missing_docs)]
+    pub fn new(
+        mut create_shape: impl FnMut() -> Result<WaveShape>,
+        chord: &CoreChord,
+        consts: &Consts,
+    ) -> Result<Combiner> {
+        let voices = chord
+            .frequencies(consts)
+            .into_iter()
+            .map(|frequency| -> Result<Box<dyn Player>> {
+                Ok(Box::new(Wave::with_shape(
+                    create_shape()?,
+                    Box::new(Constant::new(frequency)),
+                )))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Combiner::player(voices))
+    }
+}
+
+impl SpecType<Combiner> for Chord {
+    fn name() -> String {
+        "chord".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![FN.to_description(), CHORD.to_description()]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Combiner> {
+        let function = FN.get(&mut spec, consts)?;
+        let chord = CHORD.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        Chord::new(|| function.clone().into_type(consts), &chord, consts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::tree::Tree;
+    use core::Playable;
+    use core::State;
+    use inputs::Function;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn test_voices() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let chord = Chord::new(
+            || Ok(WaveShape::Input(Box::new(Function::default()))),
+            &"a4 maj7".parse().unwrap(),
+            &consts,
+        )
+        .unwrap();
+        assert_eq!(chord.get_children().len(), 4);
+
+        let mut chord = Chord::from_spec(
+            Spec::empty().with("chord".into(), "a4 maj".to_string()),
+            &consts,
+        )
+        .unwrap();
+        let mut block = vec![Playable::zero(); 100];
+        chord.play_block(&State::initial(consts.clone()), &mut block);
+        assert_eq!(chord.get_children().len(), 3);
+        assert!(block.iter().any(|p| p.get_value() != 0.0));
+    }
+}
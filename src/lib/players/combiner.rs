@@ -18,12 +18,17 @@ field_decl!(
 /// Sum several children `Player` output into one output
 pub struct Combiner {
     children: Vec<Box<dyn Player>>,
+    /// Reused for each child when playing blocks
+    child_block: Vec<Playable>,
 }
 
 impl Combiner {
     #[allow(missing_docs)]
     pub fn player(children: Vec<Box<dyn Player>>) -> Self {
-        Combiner { children }
+        Combiner {
+            children,
+            child_block: Vec::new(),
+        }
     }
 }
 
@@ -31,6 +36,19 @@ impl Player for Combiner {
     fn play(&mut self, state: &State) -> Playable {
         self.children.iter_mut().map(|p| p.play(state)).sum()
     }
+
+    fn play_block(&mut self, state: &State, block: &mut [Playable]) {
+        for playable in block.iter_mut() {
+            *playable = Playable::zero();
+        }
+        self.child_block.resize(block.len(), Playable::zero());
+        for child in &mut self.children {
+            child.play_block(state, &mut self.child_block);
+            for (playable, child_playable) in block.iter_mut().zip(&self.child_block) {
+                *playable = *playable + *child_playable;
+            }
+        }
+    }
 }
 
 impl Tree for Combiner {
@@ -58,3 +76,40 @@ impl SpecType for Combiner {
         Ok(Combiner::player(children))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use inputs::Function;
+    use players::Volume;
+    use players::Wave;
+
+    use std::sync::Arc;
+
+    fn create_combiner() -> Combiner {
+        let wave = |frequency| -> Box<dyn Player> {
+            Box::new(Wave::new(Box::new(Function::default()), frequency))
+        };
+        Combiner::player(vec![
+            wave(440.0),
+            Box::new(Volume::player(wave(523.25), Box::new(Function::default()))),
+        ])
+    }
+
+    #[test]
+    fn test_play_block() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut block_combiner = create_combiner();
+        let mut combiner = create_combiner();
+
+        let mut state = State::initial(consts);
+        let mut block = vec![Playable::zero(); 100];
+        for _ in 0..10 {
+            block_combiner.play_block(&state, &mut block);
+            for playable in &block {
+                assert_eq!(*playable, combiner.play(&state));
+                state.increment();
+            }
+        }
+    }
+}
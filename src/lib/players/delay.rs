@@ -0,0 +1,197 @@
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::tree::Tree;
+use core::Consts;
+use core::Input;
+use core::Playable;
+use core::Player;
+use core::State;
+use core::Time;
+use error::*;
+use inputs::Constant;
+
+field_decl!(CHILD, Box<dyn Player>, "Child to echo");
+field_decl!(DELAY, Time, "Time between each echo");
+field_decl!(
+    FEEDBACK,
+    Box<dyn Input>,
+    "How much of each echo is fed back into the next echo",
+    |_| Box::new(Constant::new(0.5)) as Box<dyn Input>
+);
+field_decl!(
+    MIX,
+    Box<dyn Input>,
+    "How much of the echoes are mixed with the child, from 0 (dry) to 1 (wet)",
+    |_| Box::new(Constant::new(0.5)) as Box<dyn Input>
+);
+
+/// Echo a child player
+///
+/// The echoes are kept in a ring buffer indexed by the ticks the delay is
+/// played at, so the delay follows any change in speed of its parents. Delays
+/// in beats or bars follow changes in tempo.
+pub struct Delay {
+    child: Box<dyn Player>,
+    delay: Time,
+    feedback: Box<dyn Input>,
+    mix: Box<dyn Input>,
+    buffer: Vec<Playable>,
+    /// The last tick played, and what was played for it
+    last_played: Option<(usize, Playable)>,
+}
+
+impl Delay {
+    #[allow(missing_docs)]
+    pub fn player(
+        child: Box<dyn Player>,
+        delay: &Time,
+        feedback: Box<dyn Input>,
+        mix: Box<dyn Input>,
+        consts: &Consts,
+    ) -> Result<Delay> {
+        if delay.max_ticks(consts) == 0 {
+            bail!(ErrorKind::SpecError(
+                "Delay must be at least one tick".into()
+            ));
+        }
+        Ok(Delay {
+            child,
+            delay: delay.clone(),
+            feedback,
+            mix,
+            // Long enough to hold the delay at the slowest tempo
+            buffer: vec![Playable::zero(); delay.max_ticks(consts) + 1],
+            last_played: None,
+        })
+    }
+
+    fn play_tick(&mut self, state: &State) -> Playable {
+        let tick = state.tick();
+        let delay_ticks = self.delay.ticks_before(tick, &state.consts).max(1);
+        let dry = self.child.play(state);
+        let delayed = if delay_ticks <= tick {
+            self.buffer[(tick - delay_ticks) % self.buffer.len()]
+        } else {
+            Playable::zero()
+        };
+        let index = tick % self.buffer.len();
+        self.buffer[index] = dry + delayed * self.feedback.get(state);
+        let mix = self.mix.get(state);
+        dry * (1.0 - mix) + delayed * mix
+    }
+
+    fn clear(&mut self) {
+        for playable in self.buffer.iter_mut() {
+            *playable = Playable::zero();
+        }
+    }
+}
+
+impl Player for Delay {
+    fn play(&mut self, state: &State) -> Playable {
+        let tick = state.tick();
+        match self.last_played {
+            // Played slower than real time, so repeat the last tick
+            Some((last_tick, played)) if last_tick == tick => return played,
+            // Played faster than real time, so fill in the skipped ticks
+            Some((last_tick, _)) if last_tick < tick && tick - last_tick <= self.buffer.len() => {
+                for skipped_tick in last_tick + 1..tick {
+                    self.play_tick(&state.with_tick(skipped_tick));
+                }
+            }
+            // Jumped somewhere else in the composition, so forget the echoes
+            Some(_) => self.clear(),
+            None => {}
+        }
+        let played = self.play_tick(state);
+        self.last_played = Some((tick, played));
+        played
+    }
+}
+
+impl Tree for Delay {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![
+            self.child.to_tree(),
+            self.feedback.to_tree(),
+            self.mix.to_tree(),
+        ]
+    }
+}
+
+impl SpecType for Delay {
+    fn name() -> String {
+        "delay".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![
+            CHILD.to_description(),
+            DELAY.to_description(),
+            FEEDBACK.to_description(),
+            MIX.to_description(),
+        ]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Self> {
+        let child = CHILD.get(&mut spec, consts)?;
+        let delay = DELAY.get(&mut spec, consts)?;
+        let feedback = FEEDBACK.get(&mut spec, consts)?;
+        let mix = MIX.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        Delay::player(child, &delay, feedback, mix, consts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use players::test_util::{play, Impulse};
+    use players::Speed;
+
+    use std::sync::Arc;
+
+    fn create_delay(consts: &Consts) -> Delay {
+        Delay::player(
+            Box::new(Impulse),
+            &Time::Ticks(10),
+            Box::new(Constant::new(0.5)),
+            Box::new(Constant::new(1.0)),
+            consts,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_echoes() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let played = play(&mut create_delay(&consts), consts, 31);
+        assert_eq!(played[0], 0.0);
+        assert_eq!(played[10], 1.0);
+        assert_eq!(played[20], 0.5);
+        assert_eq!(played[30], 0.25);
+        assert_eq!(played.iter().sum::<f64>(), 1.75);
+    }
+
+    #[test]
+    fn test_echoes_with_speed() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut fast = Speed::player(Box::new(create_delay(&consts)), 2.0).unwrap();
+        let played = play(&mut fast, consts.clone(), 16);
+        assert_eq!(played[5], 1.0);
+        assert_eq!(played[10], 0.5);
+        assert_eq!(played[15], 0.25);
+
+        let mut slow = Speed::player(Box::new(create_delay(&consts)), 0.5).unwrap();
+        let played = play(&mut slow, consts, 42);
+        assert_eq!(played[20], 1.0);
+        assert_eq!(played[21], 1.0);
+        assert_eq!(played[40], 0.5);
+    }
+}
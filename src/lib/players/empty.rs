@@ -20,7 +20,7 @@ impl Empty {
 
 impl Player for Empty {
     fn play(&mut self, _state: &State) -> Playable {
-        Playable::new(0)
+        Playable::zero()
     }
 }
 
@@ -0,0 +1,264 @@
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::tree::Tree;
+use core::Consts;
+use core::Input;
+use core::Playable;
+use core::Player;
+use core::State;
+use core::NUM_CHANNELS;
+use error::*;
+use inputs::Constant;
+
+use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+field_decl!(CHILD, Box<dyn Player>, "Child to filter");
+field_decl!(
+    TYPE,
+    String,
+    "Type of the filter, one of low-pass, high-pass, band-pass or notch",
+    |_| "low-pass".to_string()
+);
+field_decl!(CUTOFF, Box<dyn Input>, "Cutoff frequency of the filter in Hz");
+field_decl!(
+    RESONANCE,
+    Box<dyn Input>,
+    "Resonance (Q) of the filter, higher values emphasise the cutoff",
+    |_| Box::new(Constant::new(FRAC_1_SQRT_2)) as Box<dyn Input>
+);
+
+/// The frequency response of a [`Filter`](struct.Filter.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterType {
+    /// Removes frequencies above the cutoff
+    LowPass,
+    /// Removes frequencies below the cutoff
+    HighPass,
+    /// Removes frequencies away from the cutoff
+    BandPass,
+    /// Removes frequencies close to the cutoff
+    Notch,
+}
+
+impl FilterType {
+    fn from_string(string: &str) -> Result<FilterType> {
+        match string {
+            "low-pass" => Ok(FilterType::LowPass),
+            "high-pass" => Ok(FilterType::HighPass),
+            "band-pass" => Ok(FilterType::BandPass),
+            "notch" => Ok(FilterType::Notch),
+            filter_type => Err(ErrorKind::SpecError(format!(
+                "Unrecognized filter type: {}",
+                filter_type
+            ))
+            .into()),
+        }
+    }
+}
+
+/// Normalized biquad coefficients, i.e. divided by `a0`
+#[derive(Clone, Copy, Default)]
+struct Coefficients {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+}
+
+impl Coefficients {
+    /// Coefficients from the Audio EQ Cookbook
+    fn new(filter_type: FilterType, cutoff: f64, resonance: f64, sample_hz: f64) -> Self {
+        let cutoff = cutoff.clamp(1.0, sample_hz * 0.49);
+        let resonance = resonance.max(0.01);
+        let omega = 2.0 * PI * cutoff / sample_hz;
+        let alpha = omega.sin() / (2.0 * resonance);
+        let cos = omega.cos();
+        let (b0, b1, b2) = match filter_type {
+            FilterType::LowPass => ((1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0),
+            FilterType::HighPass => ((1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0),
+            FilterType::BandPass => (alpha, 0.0, -alpha),
+            FilterType::Notch => (1.0, -2.0 * cos, 1.0),
+        };
+        let (a0, a1, a2) = (1.0 + alpha, -2.0 * cos, 1.0 - alpha);
+        Coefficients {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// Filter the frequencies of a child player
+pub struct Filter {
+    child: Box<dyn Player>,
+    filter_type: FilterType,
+    cutoff: Box<dyn Input>,
+    resonance: Box<dyn Input>,
+    coefficients: Coefficients,
+    /// The cutoff and resonance the coefficients were calculated for
+    coefficient_inputs: Option<(f64, f64)>,
+    /// Previous two inputs for each channel
+    inputs: [[f64; 2]; NUM_CHANNELS],
+    /// Previous two outputs for each channel
+    outputs: [[f64; 2]; NUM_CHANNELS],
+    /// Reused for the inputs when playing blocks
+    cutoff_block: Vec<f64>,
+    resonance_block: Vec<f64>,
+}
+
+impl Filter {
+    #[allow(missing_docs)]
+    pub fn player(
+        child: Box<dyn Player>,
+        filter_type: FilterType,
+        cutoff: Box<dyn Input>,
+        resonance: Box<dyn Input>,
+    ) -> Filter {
+        Filter {
+            child,
+            filter_type,
+            cutoff,
+            resonance,
+            coefficients: Coefficients::default(),
+            coefficient_inputs: None,
+            inputs: [[0.0; 2]; NUM_CHANNELS],
+            outputs: [[0.0; 2]; NUM_CHANNELS],
+            cutoff_block: Vec::new(),
+            resonance_block: Vec::new(),
+        }
+    }
+
+    fn filter(
+        &mut self,
+        playable: Playable,
+        cutoff: f64,
+        resonance: f64,
+        sample_hz: f64,
+    ) -> Playable {
+        if self.coefficient_inputs != Some((cutoff, resonance)) {
+            self.coefficients = Coefficients::new(self.filter_type, cutoff, resonance, sample_hz);
+            self.coefficient_inputs = Some((cutoff, resonance));
+        }
+        let c = self.coefficients;
+
+        let mut values = [0.0; NUM_CHANNELS];
+        for (channel, value) in values.iter_mut().enumerate() {
+            let x = playable.get_channel(channel);
+            let [x1, x2] = self.inputs[channel];
+            let [y1, y2] = self.outputs[channel];
+            let y = c.b0 * x + c.b1 * x1 + c.b2 * x2 - c.a1 * y1 - c.a2 * y2;
+            self.inputs[channel] = [x, x1];
+            self.outputs[channel] = [y, y1];
+            *value = y;
+        }
+        Playable::from_channels(values)
+    }
+}
+
+impl Player for Filter {
+    fn play(&mut self, state: &State) -> Playable {
+        let played = self.child.play(state);
+        let cutoff = self.cutoff.get(state);
+        let resonance = self.resonance.get(state);
+        self.filter(played, cutoff, resonance, state.consts.sample_hz)
+    }
+
+    fn play_block(&mut self, state: &State, block: &mut [Playable]) {
+        self.child.play_block(state, block);
+        self.cutoff_block.resize(block.len(), 0.0);
+        self.resonance_block.resize(block.len(), 0.0);
+        self.cutoff.get_block(state, &mut self.cutoff_block);
+        self.resonance.get_block(state, &mut self.resonance_block);
+        for (i, playable) in block.iter_mut().enumerate() {
+            *playable = self.filter(
+                *playable,
+                self.cutoff_block[i],
+                self.resonance_block[i],
+                state.consts.sample_hz,
+            );
+        }
+    }
+}
+
+impl Tree for Filter {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![
+            self.child.to_tree(),
+            self.cutoff.to_tree(),
+            self.resonance.to_tree(),
+        ]
+    }
+}
+
+impl SpecType for Filter {
+    fn name() -> String {
+        "filter".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![
+            CHILD.to_description(),
+            TYPE.to_description(),
+            CUTOFF.to_description(),
+            RESONANCE.to_description(),
+        ]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Self> {
+        let child = CHILD.get(&mut spec, consts)?;
+        let filter_type = FilterType::from_string(&TYPE.get(&mut spec, consts)?)?;
+        let cutoff = CUTOFF.get(&mut spec, consts)?;
+        let resonance = RESONANCE.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        Ok(Filter::player(child, filter_type, cutoff, resonance))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use inputs::Function;
+    use players::Wave;
+
+    use std::sync::Arc;
+
+    /// Get the peak amplitude of a filtered wave, after the filter settles
+    fn filtered_peak(filter_type: FilterType, frequency: f64) -> f64 {
+        let consts = Arc::new(Consts::default().unwrap());
+        let wave = Wave::new(Box::new(Function::default()), frequency);
+        let mut filter = Filter::player(
+            Box::new(wave),
+            filter_type,
+            Box::new(Constant::new(1000.0)),
+            Box::new(Constant::new(FRAC_1_SQRT_2)),
+        );
+        let mut block = vec![Playable::zero(); 44100];
+        filter.play_block(&State::initial(consts.clone()), &mut block);
+        let peak = block[22050..]
+            .iter()
+            .map(|p| p.get_value().abs())
+            .fold(0.0, f64::max);
+        peak / consts.loudness_factor
+    }
+
+    #[test]
+    fn test_filter_types() {
+        assert!(filtered_peak(FilterType::LowPass, 100.0) > 0.95);
+        assert!(filtered_peak(FilterType::LowPass, 10000.0) < 0.05);
+        assert!(filtered_peak(FilterType::HighPass, 100.0) < 0.05);
+        assert!(filtered_peak(FilterType::HighPass, 10000.0) > 0.95);
+        assert!(filtered_peak(FilterType::BandPass, 1000.0) > 0.95);
+        assert!(filtered_peak(FilterType::BandPass, 10000.0) < 0.2);
+        assert!(filtered_peak(FilterType::Notch, 1000.0) < 0.05);
+        assert!(filtered_peak(FilterType::Notch, 100.0) > 0.95);
+    }
+}
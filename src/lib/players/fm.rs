@@ -0,0 +1,167 @@
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::tree::Tree;
+use core::Consts;
+use core::Input;
+use core::Playable;
+use core::Player;
+use core::State;
+use error::*;
+use inputs::Constant;
+use players::Wave;
+
+use std::f64::consts::PI;
+
+field_decl!(
+    CARRIER,
+    Spec,
+    "Wave that is heard, with the same fields as a wave player"
+);
+field_decl!(
+    MODULATOR,
+    Spec,
+    "Wave whose output moves the carrier's phase, with the same fields as a wave player"
+);
+field_decl!(
+    INDEX,
+    Box<dyn Input>,
+    "Modulation index, how many radians the carrier's phase moves at the modulator's peak",
+    |_| Box::new(Constant::new(1.0)) as Box<dyn Input>
+);
+
+/// Frequency modulation synthesis, where a modulator wave moves the phase of a
+/// carrier wave
+///
+/// The modulator is a wave so that its peak is known, making the index the
+/// most the carrier's phase moves.
+pub struct Fm {
+    carrier: Wave,
+    modulator: Wave,
+    index: Box<dyn Input>,
+}
+
+impl Fm {
+    #[allow(missing_docs)]
+    pub fn player(carrier: Wave, modulator: Wave, index: Box<dyn Input>) -> Fm {
+        Fm {
+            carrier,
+            modulator,
+            index,
+        }
+    }
+}
+
+impl Player for Fm {
+    fn play(&mut self, state: &State) -> Playable {
+        // Waves are scaled by the loudness factor, so undo it to get the
+        // modulator in [-1, 1]
+        let modulation = self.modulator.play(state).get_value() / state.consts.loudness_factor;
+        let offset = self.index.get(state) * modulation / (2.0 * PI);
+        self.carrier.play_with_phase_offset(state, offset)
+    }
+}
+
+impl Tree for Fm {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![
+            self.carrier.to_tree(),
+            self.modulator.to_tree(),
+            self.index.to_tree(),
+        ]
+    }
+}
+
+impl SpecType for Fm {
+    fn name() -> String {
+        "fm".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![
+            CARRIER.to_description(),
+            MODULATOR.to_description(),
+            INDEX.to_description(),
+        ]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Fm> {
+        let carrier = wave_from_spec(CARRIER.get(&mut spec, consts)?, consts)
+            .chain_err(|| "Failed to create carrier")?;
+        let modulator = wave_from_spec(MODULATOR.get(&mut spec, consts)?, consts)
+            .chain_err(|| "Failed to create modulator")?;
+        let index = INDEX.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        Ok(Fm::player(carrier, modulator, index))
+    }
+}
+
+/// Create a wave from a spec, which can leave out its name
+fn wave_from_spec(mut spec: Spec, consts: &Consts) -> Result<Wave> {
+    if let Some(name) = spec.consume_optional::<String>("name", consts)? {
+        if name != Wave::name() {
+            bail!(ErrorKind::SpecError(format!(
+                "Expected a {}, got: {}",
+                Wave::name(),
+                name
+            )));
+        }
+    }
+    Wave::from_spec(spec, consts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use inputs::Function;
+    use players::test_util::play;
+
+    use std::sync::Arc;
+
+    fn create_fm(index: f64) -> Fm {
+        let wave = |frequency| Wave::new(Box::new(Function::default()), frequency);
+        Fm::player(wave(441.0), wave(110.25), Box::new(Constant::new(index)))
+    }
+
+    #[test]
+    fn test_modulation() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut carrier = Wave::new(Box::new(Function::default()), 441.0);
+        let expected = play(&mut carrier, consts.clone(), 400);
+
+        // No modulation plays the carrier
+        let played = play(&mut create_fm(0.0), consts.clone(), 400);
+        assert_eq!(played, expected);
+
+        // The carrier's phase is moved by the index times the modulator, in
+        // radians
+        let played = play(&mut create_fm(2.0), consts.clone(), 400);
+        for (tick, value) in played.iter().enumerate() {
+            let modulation = (2.0 * PI * tick as f64 / 400.0).sin();
+            let phase = 2.0 * PI * tick as f64 / 100.0 + 2.0 * modulation;
+            let expected = phase.sin() * consts.loudness_factor;
+            assert!((value - expected).abs() < 1e-6, "{} != {}", value, expected);
+        }
+    }
+
+    #[test]
+    fn test_spec() {
+        let consts = Consts::default().unwrap();
+        let fm = |carrier: Spec| {
+            let spec = Spec::empty().with("carrier".into(), carrier).with(
+                "modulator".into(),
+                Spec::empty().with("frequency".into(), 110.0),
+            );
+            Fm::from_spec(spec, &consts)
+        };
+        let carrier = Spec::empty().with("frequency".into(), 440.0);
+        assert!(fm(carrier.clone()).is_ok());
+        assert!(fm(carrier.clone().with("name".into(), "wave".to_string())).is_ok());
+        assert!(fm(carrier.with("name".into(), "sample".to_string())).is_err());
+    }
+}
@@ -20,7 +20,7 @@ field_decl!(
 pub struct FourierDrawer {
     player: Box<dyn Player>,
     buffer_size: usize,
-    buffer: Vec<i32>,
+    buffer: Vec<f64>,
     sample_bucketer: SampleBucketer,
 }
 
@@ -0,0 +1,292 @@
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::tree::Tree;
+use core::Consts;
+use core::Input;
+use core::Playable;
+use core::Player;
+use core::State;
+use core::Time;
+use error::*;
+use inputs::Constant;
+use players::sample::WavRegion;
+use players::Frames;
+
+use std::f64::consts::PI;
+
+use rand;
+use rand::{Rng, XorShiftRng};
+
+/// Most grains that can play at once, further grains are skipped
+const MAX_GRAINS: usize = 256;
+
+field_decl!(
+    PATH,
+    String,
+    "Path of the .wav file to take grains from, relative to the sample-dir const"
+);
+field_decl!(
+    START,
+    Time,
+    "Start of the sample in the .wav file, in seconds or ticks"
+);
+field_decl!(
+    DURATION,
+    Time,
+    "Duration of the sample in the .wav file, in seconds or ticks"
+);
+field_decl!(
+    POSITION,
+    Box<dyn Input>,
+    "Where grains start in the sample, from 0 at the start to 1 at the end",
+    |_| Box::new(Constant::new(0.0)) as Box<dyn Input>
+);
+field_decl!(
+    SIZE,
+    Box<dyn Input>,
+    "How long each grain lasts in seconds",
+    |_| Box::new(Constant::new(0.1)) as Box<dyn Input>
+);
+field_decl!(
+    DENSITY,
+    Box<dyn Input>,
+    "How many grains start every second",
+    |_| Box::new(Constant::new(20.0)) as Box<dyn Input>
+);
+field_decl!(
+    PITCH,
+    Box<dyn Input>,
+    "How many semitones grains are transposed by",
+    |_| Box::new(Constant::new(0.0)) as Box<dyn Input>
+);
+field_decl!(
+    SPRAY,
+    Box<dyn Input>,
+    "How far each grain's position is randomly moved, as a fraction of the sample",
+    |_| Box::new(Constant::new(0.0)) as Box<dyn Input>
+);
+
+/// A short window of the sample being played
+struct Grain {
+    /// Position in the sample in frames
+    position: f64,
+    /// How many frames are played every tick
+    rate: f64,
+    age_ticks: usize,
+    length_ticks: usize,
+}
+
+/// Play overlapping windowed grains from a .wav file
+///
+/// The grain's position, size, pitch and spray are read when each grain
+/// starts.
+pub struct Granular {
+    frames: Frames,
+    /// How many frames in the sample are played every tick at the original
+    /// pitch
+    frames_per_tick: f64,
+    position: Box<dyn Input>,
+    size: Box<dyn Input>,
+    density: Box<dyn Input>,
+    pitch: Box<dyn Input>,
+    spray: Box<dyn Input>,
+    grains: Vec<Grain>,
+    /// Progress towards starting the next grain, starting one when reaching 1
+    next_grain_phase: f64,
+    rng: XorShiftRng,
+}
+
+impl Granular {
+    #[allow(missing_docs)]
+    pub fn new(
+        frames: Frames,
+        frames_per_tick: f64,
+        position: Box<dyn Input>,
+        size: Box<dyn Input>,
+        density: Box<dyn Input>,
+        pitch: Box<dyn Input>,
+        spray: Box<dyn Input>,
+    ) -> Result<Granular> {
+        if frames.is_empty() {
+            bail!(ErrorKind::SpecError("Granular sample has no frames".into()));
+        }
+        Ok(Granular {
+            frames,
+            frames_per_tick,
+            position,
+            size,
+            density,
+            pitch,
+            spray,
+            grains: Vec::new(),
+            next_grain_phase: 1.0,
+            rng: rand::weak_rng(),
+        })
+    }
+
+    fn start_grain(&mut self, state: &State) {
+        let num_frames = self.frames.len() as f64;
+        let spray = self.spray.get(state) * self.rng.gen_range(-1.0, 1.0);
+        let position = ((self.position.get(state) + spray) * num_frames).rem_euclid(num_frames);
+        let length_ticks = (self.size.get(state) * state.consts.sample_hz).max(0.0) as usize;
+        let rate = self.frames_per_tick * 2_f64.powf(self.pitch.get(state) / 12.0);
+        if length_ticks > 0 && self.grains.len() < MAX_GRAINS {
+            self.grains.push(Grain {
+                position,
+                rate,
+                age_ticks: 0,
+                length_ticks,
+            });
+        }
+    }
+
+    /// Linearly interpolate between frames, wrapping around the end of the
+    /// sample
+    fn frame(&self, position: f64) -> Playable {
+        let index = position.floor();
+        let fraction = position - index;
+        let index = index as usize % self.frames.len();
+        let next_index = (index + 1) % self.frames.len();
+        self.frames[index] * (1.0 - fraction) + self.frames[next_index] * fraction
+    }
+}
+
+impl Player for Granular {
+    fn play(&mut self, state: &State) -> Playable {
+        self.next_grain_phase += self.density.get(state).max(0.0) / state.consts.sample_hz;
+        while self.next_grain_phase >= 1.0 {
+            self.next_grain_phase -= 1.0;
+            self.start_grain(state);
+        }
+
+        let mut playable = Playable::zero();
+        for grain in &self.grains {
+            let progress = grain.age_ticks as f64 / grain.length_ticks as f64;
+            let window = 0.5 - 0.5 * (2.0 * PI * progress).cos();
+            let position = grain.position + grain.age_ticks as f64 * grain.rate;
+            playable = playable + self.frame(position) * window;
+        }
+
+        for grain in &mut self.grains {
+            grain.age_ticks += 1;
+        }
+        self.grains
+            .retain(|grain| grain.age_ticks < grain.length_ticks);
+        playable
+    }
+}
+
+impl Tree for Granular {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![
+            self.position.to_tree(),
+            self.size.to_tree(),
+            self.density.to_tree(),
+            self.pitch.to_tree(),
+            self.spray.to_tree(),
+        ]
+    }
+}
+
+impl SpecType for Granular {
+    fn name() -> String {
+        "granular".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![
+            PATH.to_description(),
+            START.to_description(),
+            DURATION.to_description(),
+            POSITION.to_description(),
+            SIZE.to_description(),
+            DENSITY.to_description(),
+            PITCH.to_description(),
+            SPRAY.to_description(),
+        ]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Granular> {
+        let wav_path = PATH.get(&mut spec, consts)?;
+        let start = START.get(&mut spec, consts)?;
+        let duration = DURATION.get(&mut spec, consts)?;
+        let position = POSITION.get(&mut spec, consts)?;
+        let size = SIZE.get(&mut spec, consts)?;
+        let density = DENSITY.get(&mut spec, consts)?;
+        let pitch = PITCH.get(&mut spec, consts)?;
+        let spray = SPRAY.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        let region = WavRegion::load(&wav_path, &start, &duration, consts)?;
+        Granular::new(
+            region.frames.clone(),
+            region.sample_hz / consts.sample_hz,
+            position,
+            size,
+            density,
+            pitch,
+            spray,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use players::test_util::play;
+
+    use std::sync::Arc;
+
+    fn create_granular(density: f64, pitch: f64) -> Granular {
+        let frames = (0..100).map(|i| Playable::new(f64::from(i))).collect();
+        Granular::new(
+            Frames::new(frames),
+            1.0,
+            Box::new(Constant::new(0.5)),
+            Box::new(Constant::new(10.0 / 44100.0)),
+            Box::new(Constant::new(density)),
+            Box::new(Constant::new(pitch)),
+            Box::new(Constant::new(0.0)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_grain() {
+        let consts = Arc::new(Consts::default().unwrap());
+        // A single grain of 10 ticks from the middle of the sample
+        let played = play(&mut create_granular(1.0, 0.0), consts.clone(), 20);
+        assert_eq!(played[0], 0.0);
+        assert!((played[5] - 55.0).abs() < 1e-9);
+        assert!(played[1] > 0.0 && played[1] < 51.0);
+        assert!(played[10..].iter().all(|value| *value == 0.0));
+
+        // Transposed up an octave
+        let played = play(&mut create_granular(1.0, 12.0), consts.clone(), 20);
+        assert!((played[5] - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_density() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut granular = create_granular(44100.0 / 4.0, 0.0);
+        play(&mut granular, consts.clone(), 100);
+        // Grains started on ticks 91, 95 and 99 are still playing
+        assert_eq!(granular.grains.len(), 3);
+        assert!(Granular::new(
+            Frames::new(vec![]),
+            1.0,
+            Box::new(Constant::new(0.0)),
+            Box::new(Constant::new(0.0)),
+            Box::new(Constant::new(0.0)),
+            Box::new(Constant::new(0.0)),
+            Box::new(Constant::new(0.0)),
+        )
+        .is_err());
+    }
+}
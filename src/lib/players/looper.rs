@@ -0,0 +1,133 @@
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::tree::Tree;
+use core::Consts;
+use core::Playable;
+use core::Player;
+use core::State;
+use core::Time;
+use error::*;
+
+field_decl!(CHILD, Box<dyn Player>, "Child to loop");
+field_decl!(START, Time, "Where in the child the loop starts", |_| {
+    Time::zero()
+});
+field_decl!(LENGTH, Time, "How long the loop lasts before wrapping");
+
+/// Repeat a region of a child player
+pub struct Loop {
+    child: Box<dyn Player>,
+    start_milli_tick: usize,
+    length_milli_tick: usize,
+}
+
+impl Loop {
+    #[allow(missing_docs)]
+    pub fn player(
+        child: Box<dyn Player>,
+        start: &Time,
+        length: &Time,
+        consts: &Consts,
+    ) -> Result<Loop> {
+        let start_tick = start.to_ticks(consts);
+        let length_tick = length.ticks_after(start_tick, consts);
+        if length_tick == 0 {
+            bail!(ErrorKind::SpecError("Loop length must be positive".into()));
+        }
+        Ok(Loop {
+            child,
+            start_milli_tick: start_tick * 1000,
+            length_milli_tick: length_tick * 1000,
+        })
+    }
+}
+
+impl Player for Loop {
+    fn play(&mut self, state: &State) -> Playable {
+        let milli_tick = self.start_milli_tick + state.milli_tick % self.length_milli_tick;
+        self.child.play(&state.with_milli_tick(milli_tick))
+    }
+}
+
+impl Tree for Loop {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![self.child.to_tree()]
+    }
+}
+
+impl SpecType for Loop {
+    fn name() -> String {
+        "loop".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![
+            CHILD.to_description(),
+            START.to_description(),
+            LENGTH.to_description(),
+        ]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Loop> {
+        let child = CHILD.get(&mut spec, consts)?;
+        let start = START.get(&mut spec, consts)?;
+        let length = LENGTH.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        Loop::player(child, &start, &length, consts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use players::test_util::{play, Counter};
+    use players::Speed;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn test_loop() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut looped = Loop::player(
+            Box::new(Counter(0.0)),
+            &Time::Ticks(5),
+            &Time::Ticks(3),
+            &consts,
+        )
+        .unwrap();
+        assert_eq!(
+            play(&mut looped, consts.clone(), 7),
+            vec![5.0, 6.0, 7.0, 5.0, 6.0, 7.0, 5.0]
+        );
+        assert!(Loop::player(
+            Box::new(Counter(0.0)),
+            &Time::zero(),
+            &Time::zero(),
+            &consts
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_speed() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let looped = Loop::player(
+            Box::new(Counter(0.0)),
+            &Time::zero(),
+            &Time::Ticks(4),
+            &consts,
+        )
+        .unwrap();
+        let mut sped_up = Speed::player(Box::new(looped), 0.5).unwrap();
+        assert_eq!(
+            play(&mut sped_up, consts, 10),
+            vec![0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 0.0, 0.0]
+        );
+    }
+}
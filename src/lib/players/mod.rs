@@ -2,31 +2,59 @@
 
 use core::Player;
 
+mod adsr;
+mod chord;
 mod combiner;
+mod delay;
 mod empty;
+mod filter;
+mod fm;
 mod fourier_drawer;
+mod granular;
 mod keyboard;
 mod linear;
+mod looper;
+mod noise;
 mod one_off;
+mod oscillator;
+mod pan;
 mod play_input;
+mod reverb;
 mod sample;
+mod sequence;
 mod speed;
+#[cfg(test)]
+mod test_util;
 mod volume;
 mod wave;
 mod wave_drawer;
+mod wavetable;
 
+pub use self::adsr::Adsr;
+pub use self::chord::Chord;
 pub use self::combiner::Combiner;
+pub use self::delay::Delay;
 pub use self::empty::Empty;
+pub use self::filter::{Filter, FilterType};
+pub use self::fm::Fm;
 pub use self::fourier_drawer::FourierDrawer;
+pub use self::granular::Granular;
 pub use self::keyboard::Keyboard;
 pub use self::linear::Linear;
+pub use self::looper::Loop;
+pub use self::noise::Noise;
 pub use self::one_off::OneOff;
+pub use self::oscillator::{Oscillator, OscillatorShape};
+pub use self::pan::Pan;
 pub use self::play_input::PlayInput;
-pub use self::sample::Sample;
+pub use self::reverb::Reverb;
+pub use self::sample::{Frames, Sample};
+pub use self::sequence::{Section, Sequence};
 pub use self::speed::Speed;
 pub use self::volume::Volume;
-pub use self::wave::Wave;
+pub use self::wave::{Wave, WaveShape};
 pub use self::wave_drawer::WaveDrawer;
+pub use self::wavetable::{Wavetable, WavetableShape};
 
 impl_super_from_value!(
     dyn Player,
@@ -41,5 +69,17 @@ impl_super_from_value!(
     Empty,
     Linear,
     OneOff,
-    FourierDrawer
+    FourierDrawer,
+    Pan,
+    Adsr,
+    Filter,
+    Delay,
+    Reverb,
+    Chord,
+    Sequence,
+    Loop,
+    Granular,
+    Fm,
+    Noise,
+    Wavetable
 );
@@ -0,0 +1,56 @@
+use core::spec::Spec;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::tree::Tree;
+use core::Consts;
+use core::Input;
+use core::Playable;
+use core::Player;
+use core::State;
+use error::*;
+use inputs::Noise as NoiseInput;
+
+/// Play white, pink or brown noise
+///
+/// Has the same fields as the `noise` input, so a seed gives the same noise on
+/// every render.
+pub struct Noise {
+    noise: NoiseInput,
+}
+
+impl Noise {
+    #[allow(missing_docs)]
+    pub fn player(noise: NoiseInput) -> Noise {
+        Noise { noise }
+    }
+}
+
+impl Player for Noise {
+    fn play(&mut self, state: &State) -> Playable {
+        Playable::new(self.noise.get(state) * state.consts.loudness_factor)
+    }
+}
+
+impl Tree for Noise {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![self.noise.to_tree()]
+    }
+}
+
+impl SpecType for Noise {
+    fn name() -> String {
+        "noise".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        NoiseInput::field_descriptions()
+    }
+
+    fn from_spec(spec: Spec, consts: &Consts) -> Result<Noise> {
+        Ok(Noise::player(NoiseInput::from_spec(spec, consts)?))
+    }
+}
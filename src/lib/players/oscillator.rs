@@ -0,0 +1,215 @@
+use core::spec::{FromValue, Spec, Value};
+use core::tree::Tree;
+use core::Consts;
+use core::Input;
+use core::State;
+use error::*;
+use inputs::Constant;
+
+/// Shapes of band-limited oscillators
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OscillatorShape {
+    #[allow(missing_docs)]
+    Saw,
+    #[allow(missing_docs)]
+    Square,
+    #[allow(missing_docs)]
+    Triangle,
+    /// A square wave with a variable pulse width
+    Pulse,
+}
+
+impl OscillatorShape {
+    fn from_string(string: &str) -> Result<OscillatorShape> {
+        match string {
+            "saw" => Ok(OscillatorShape::Saw),
+            "square" => Ok(OscillatorShape::Square),
+            "triangle" => Ok(OscillatorShape::Triangle),
+            "pulse" => Ok(OscillatorShape::Pulse),
+            shape => Err(
+                ErrorKind::SpecError(format!("Unrecognized oscillator shape: {}", shape)).into(),
+            ),
+        }
+    }
+}
+
+/// A band-limited oscillator, using PolyBLEP to smooth jumps and PolyBLAMP to
+/// smooth corners so that high notes don't alias
+///
+/// In specs, has a `shape` of saw, square, triangle or pulse, a `pulse-width`
+/// input in [0, 1], and `bipolar` which is true by default. Can also be given
+/// as just the name of the shape.
+pub struct Oscillator {
+    shape: OscillatorShape,
+    pulse_width: Box<dyn Input>,
+    bipolar: bool,
+}
+
+impl Oscillator {
+    #[allow(missing_docs)]
+    pub fn new(shape: OscillatorShape, pulse_width: Box<dyn Input>, bipolar: bool) -> Oscillator {
+        Oscillator {
+            shape,
+            pulse_width,
+            bipolar,
+        }
+    }
+
+    /// Get the value of the oscillator at `phase` in [0, 1), where the phase
+    /// moves by `phase_step` every tick
+    pub fn get(&mut self, state: &State, phase: f64, phase_step: f64) -> f64 {
+        let dt = phase_step.abs().min(0.5);
+        let value = match self.shape {
+            OscillatorShape::Saw => 2.0 * phase - 1.0 - poly_blep(phase, dt),
+            OscillatorShape::Square => pulse(phase, 0.5, dt),
+            OscillatorShape::Pulse => {
+                let pulse_width = self.pulse_width.get(state).max(dt).min(1.0 - dt);
+                pulse(phase, pulse_width, dt)
+            }
+            OscillatorShape::Triangle => {
+                // Start at zero and rising like a sine, with corners at 0.25
+                // and 0.75
+                let phase = (phase + 0.25).fract();
+                let value = 1.0 - 4.0 * (phase - 0.5).abs();
+                // The slope changes by 8 at every corner
+                value + 4.0 * dt * (poly_blamp(phase, dt) - poly_blamp((phase + 0.5).fract(), dt))
+            }
+        };
+        if self.bipolar {
+            value
+        } else {
+            (value + 1.0) / 2.0
+        }
+    }
+}
+
+/// A pulse that is high until `pulse_width`, then low
+fn pulse(phase: f64, pulse_width: f64, dt: f64) -> f64 {
+    let value = if phase < pulse_width { 1.0 } else { -1.0 };
+    value + poly_blep(phase, dt) - poly_blep((phase + 1.0 - pulse_width).fract(), dt)
+}
+
+/// Residual of a band-limited step of 2 at phase 0, for ticks `dt` apart
+fn poly_blep(phase: f64, dt: f64) -> f64 {
+    if phase < dt {
+        let t = phase / dt;
+        2.0 * t - t * t - 1.0
+    } else if phase > 1.0 - dt {
+        let t = (phase - 1.0) / dt;
+        t * t + 2.0 * t + 1.0
+    } else {
+        0.0
+    }
+}
+
+/// Residual of a band-limited change in slope of 2 per tick at phase 0, for
+/// ticks `dt` apart
+fn poly_blamp(phase: f64, dt: f64) -> f64 {
+    if phase < dt {
+        let t = phase / dt - 1.0;
+        -t * t * t / 3.0
+    } else if phase > 1.0 - dt {
+        let t = (phase - 1.0) / dt + 1.0;
+        t * t * t / 3.0
+    } else {
+        0.0
+    }
+}
+
+impl Tree for Oscillator {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![self.pulse_width.to_tree()]
+    }
+}
+
+impl FromValue for Oscillator {
+    fn name() -> String {
+        "oscillator".into()
+    }
+
+    fn from_value(value: Value, consts: &Consts) -> Result<Oscillator> {
+        if let Value::Str(shape) = value {
+            return Ok(Oscillator::new(
+                OscillatorShape::from_string(&shape)?,
+                Box::new(Constant::new(0.5)),
+                true,
+            ));
+        }
+
+        let mut spec: Spec = value.into_type(consts)?;
+        spec.consume_optional::<String>("name", consts)?;
+        let shape: String = spec.consume("shape", consts)?;
+        let pulse_width = spec.consume_with_default(
+            "pulse-width",
+            Box::new(Constant::new(0.5)) as Box<dyn Input>,
+            consts,
+        )?;
+        let bipolar = spec.consume_with_default("bipolar", true, consts)?;
+        spec.ensure_all_used()?;
+        Ok(Oscillator::new(
+            OscillatorShape::from_string(&shape)?,
+            pulse_width,
+            bipolar,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::Arc;
+
+    fn play(shape: &str, dt: f64) -> Vec<f64> {
+        let consts = Arc::new(Consts::default().unwrap());
+        let state = State::initial(consts.clone());
+        let mut oscillator = Oscillator::from_value(Value::Str(shape.into()), &consts).unwrap();
+        (0..(1.0 / dt).round() as usize)
+            .map(|i| oscillator.get(&state, (i as f64 * dt).fract(), dt))
+            .collect()
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_shapes() {
+        let saw = play("saw", 0.1);
+        // The jump is smoothed to be halfway at the start of the cycle
+        assert_close(saw[0], 0.0);
+        assert_close(saw[5], 0.0);
+        assert_close(saw[9], 0.8);
+
+        let square = play("square", 0.1);
+        assert_close(square[0], 0.0);
+        assert_close(square[2], 1.0);
+        assert_close(square[5], 0.0);
+        assert_close(square[7], -1.0);
+
+        let triangle = play("triangle", 0.125);
+        assert_close(triangle[0], 0.0);
+        assert_close(triangle[1], 0.5);
+        assert_close(triangle[4], 0.0);
+        // Corners are rounded
+        assert!(triangle[2] < 1.0 && triangle[2] > 0.5);
+        assert_close(triangle[6], -triangle[2]);
+    }
+
+    #[test]
+    fn test_pulse_width() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let state = State::initial(consts.clone());
+        let mut pulse =
+            Oscillator::new(OscillatorShape::Pulse, Box::new(Constant::new(0.25)), false);
+        let values: Vec<f64> = (0..8)
+            .map(|i| pulse.get(&state, f64::from(i) / 8.0, 0.125))
+            .collect();
+        assert_eq!(values, vec![0.5, 1.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(Oscillator::from_value(Value::Str("sine".into()), &consts).is_err());
+    }
+}
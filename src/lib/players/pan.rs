@@ -0,0 +1,71 @@
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::tree::Tree;
+use core::Consts;
+use core::Input;
+use core::Playable;
+use core::Player;
+use core::State;
+use error::*;
+
+use std::f64::consts::PI;
+
+field_decl!(CHILD, Box<dyn Player>, "Child to place in the stereo field");
+field_decl!(
+    POSITION,
+    Box<dyn Input>,
+    "Position in the stereo field, from -1 (left) to 1 (right)"
+);
+
+/// Place the mono mix of a child player in the stereo field
+///
+/// Uses constant power panning, so the child is equally loud in every position.
+pub struct Pan {
+    child: Box<dyn Player>,
+    position: Box<dyn Input>,
+}
+
+impl Pan {
+    #[allow(missing_docs)]
+    pub fn player(child: Box<dyn Player>, position: Box<dyn Input>) -> Pan {
+        Pan { child, position }
+    }
+}
+
+impl Player for Pan {
+    fn play(&mut self, state: &State) -> Playable {
+        let played = self.child.play(state).get_value();
+        let position = self.position.get(state).clamp(-1.0, 1.0);
+        let angle = (position + 1.0) * PI / 4.0;
+        Playable::stereo(played * angle.cos(), played * angle.sin())
+    }
+}
+
+impl Tree for Pan {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![self.child.to_tree(), self.position.to_tree()]
+    }
+}
+
+impl SpecType for Pan {
+    fn name() -> String {
+        "pan".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![CHILD.to_description(), POSITION.to_description()]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Self> {
+        let child = CHILD.get(&mut spec, consts)?;
+        let position = POSITION.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        Ok(Pan::player(child, position))
+    }
+}
@@ -6,26 +6,34 @@ use core::Player;
 use core::State;
 use inputs::InputMod;
 
-use std::i32;
-
 /// Play directly from a bounded input
 pub struct PlayInput {
     input: Box<dyn Input>,
+    /// Reused for the input when playing blocks
+    input_block: Vec<f64>,
 }
 
 impl PlayInput {
     #[allow(missing_docs)]
     pub fn new(input: Box<dyn Input>, consts: &Consts) -> Box<dyn Player> {
-        let mult = consts.loudness_factor * i32::MAX as f64;
         Box::new(PlayInput {
-            input: Box::new(InputMod::new(input, 0.0, mult)),
+            input: Box::new(InputMod::new(input, 0.0, consts.loudness_factor)),
+            input_block: Vec::new(),
         })
     }
 }
 
 impl Player for PlayInput {
     fn play(&mut self, state: &State) -> Playable {
-        Playable::new(self.input.get(state) as i32)
+        Playable::new(self.input.get(state))
+    }
+
+    fn play_block(&mut self, state: &State, block: &mut [Playable]) {
+        self.input_block.resize(block.len(), 0.0);
+        self.input.get_block(state, &mut self.input_block);
+        for (playable, value) in block.iter_mut().zip(&self.input_block) {
+            *playable = Playable::new(*value);
+        }
     }
 }
 
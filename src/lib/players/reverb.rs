@@ -0,0 +1,253 @@
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::tree::Tree;
+use core::Consts;
+use core::Input;
+use core::Playable;
+use core::Player;
+use core::State;
+use core::NUM_CHANNELS;
+use error::*;
+use inputs::Constant;
+
+field_decl!(CHILD, Box<dyn Player>, "Child to add reverb to");
+field_decl!(
+    ROOM_SIZE,
+    Box<dyn Input>,
+    "Size of the room, from 0 (small) to 1 (large)",
+    |_| Box::new(Constant::new(0.5)) as Box<dyn Input>
+);
+field_decl!(
+    DAMPING,
+    Box<dyn Input>,
+    "How much the walls absorb high frequencies, from 0 to 1",
+    |_| Box::new(Constant::new(0.5)) as Box<dyn Input>
+);
+field_decl!(
+    MIX,
+    Box<dyn Input>,
+    "How much of the reverb is mixed with the child, from 0 (dry) to 1 (wet)",
+    |_| Box::new(Constant::new(0.3)) as Box<dyn Input>
+);
+
+/// Comb filter lengths in ticks at 44100Hz, from Freeverb
+const COMB_TUNINGS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+/// All-pass filter lengths in ticks at 44100Hz, from Freeverb
+const ALL_PASS_TUNINGS: [usize; 4] = [556, 441, 341, 225];
+/// How many ticks longer the filters of each channel are than the previous
+/// channel, to decorrelate the channels
+const STEREO_SPREAD: usize = 23;
+const TUNING_SAMPLE_HZ: f64 = 44100.0;
+
+const INPUT_GAIN: f64 = 0.015;
+const WET_GAIN: f64 = 3.0;
+const ALL_PASS_FEEDBACK: f64 = 0.5;
+
+/// Feedback comb filter with a low pass filter in the feedback loop
+struct Comb {
+    buffer: Vec<f64>,
+    index: usize,
+    filtered: f64,
+}
+
+impl Comb {
+    fn new(length: usize) -> Self {
+        Comb {
+            buffer: vec![0.0; length],
+            index: 0,
+            filtered: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: f64, feedback: f64, damping: f64) -> f64 {
+        let output = self.buffer[self.index];
+        self.filtered = output * (1.0 - damping) + self.filtered * damping;
+        self.buffer[self.index] = input + self.filtered * feedback;
+        self.index = (self.index + 1) % self.buffer.len();
+        output
+    }
+}
+
+/// Schroeder all-pass filter, which diffuses without colouring
+struct AllPass {
+    buffer: Vec<f64>,
+    index: usize,
+}
+
+impl AllPass {
+    fn new(length: usize) -> Self {
+        AllPass {
+            buffer: vec![0.0; length],
+            index: 0,
+        }
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        let buffered = self.buffer[self.index];
+        self.buffer[self.index] = input + buffered * ALL_PASS_FEEDBACK;
+        self.index = (self.index + 1) % self.buffer.len();
+        buffered - input
+    }
+}
+
+/// Add the reverberation of a room to a child player
+///
+/// Based on Freeverb: the mono mix of the child is fed through parallel comb
+/// filters and then serial all-pass filters, with slightly different lengths
+/// for each channel.
+pub struct Reverb {
+    child: Box<dyn Player>,
+    room_size: Box<dyn Input>,
+    damping: Box<dyn Input>,
+    mix: Box<dyn Input>,
+    combs: Vec<Vec<Comb>>,
+    all_passes: Vec<Vec<AllPass>>,
+}
+
+impl Reverb {
+    #[allow(missing_docs)]
+    pub fn player(
+        child: Box<dyn Player>,
+        room_size: Box<dyn Input>,
+        damping: Box<dyn Input>,
+        mix: Box<dyn Input>,
+        consts: &Consts,
+    ) -> Reverb {
+        let scale = |length: usize, channel: usize| {
+            let length = length + channel * STEREO_SPREAD;
+            ((length as f64 * consts.sample_hz / TUNING_SAMPLE_HZ) as usize).max(1)
+        };
+        Reverb {
+            child,
+            room_size,
+            damping,
+            mix,
+            combs: (0..NUM_CHANNELS)
+                .map(|channel| {
+                    COMB_TUNINGS
+                        .iter()
+                        .map(|length| Comb::new(scale(*length, channel)))
+                        .collect()
+                })
+                .collect(),
+            all_passes: (0..NUM_CHANNELS)
+                .map(|channel| {
+                    ALL_PASS_TUNINGS
+                        .iter()
+                        .map(|length| AllPass::new(scale(*length, channel)))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Player for Reverb {
+    fn play(&mut self, state: &State) -> Playable {
+        let played = self.child.play(state);
+        let feedback = 0.7 + 0.28 * self.room_size.get(state).clamp(0.0, 1.0);
+        let damping = 0.4 * self.damping.get(state).clamp(0.0, 1.0);
+        let mix = self.mix.get(state).clamp(0.0, 1.0);
+
+        let input = played.get_value() * INPUT_GAIN;
+        let mut values = [0.0; NUM_CHANNELS];
+        for (channel, value) in values.iter_mut().enumerate() {
+            let mut wet: f64 = self.combs[channel]
+                .iter_mut()
+                .map(|comb| comb.process(input, feedback, damping))
+                .sum();
+            for all_pass in self.all_passes[channel].iter_mut() {
+                wet = all_pass.process(wet);
+            }
+            *value = wet * WET_GAIN;
+        }
+        played * (1.0 - mix) + Playable::from_channels(values) * mix
+    }
+}
+
+impl Tree for Reverb {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![
+            self.child.to_tree(),
+            self.room_size.to_tree(),
+            self.damping.to_tree(),
+            self.mix.to_tree(),
+        ]
+    }
+}
+
+impl SpecType for Reverb {
+    fn name() -> String {
+        "reverb".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![
+            CHILD.to_description(),
+            ROOM_SIZE.to_description(),
+            DAMPING.to_description(),
+            MIX.to_description(),
+        ]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Self> {
+        let child = CHILD.get(&mut spec, consts)?;
+        let room_size = ROOM_SIZE.get(&mut spec, consts)?;
+        let damping = DAMPING.get(&mut spec, consts)?;
+        let mix = MIX.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        Ok(Reverb::player(child, room_size, damping, mix, consts))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use players::test_util::Impulse;
+
+    use std::sync::Arc;
+
+    fn play_impulse(room_size: f64, mix: f64) -> Vec<Playable> {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut reverb = Reverb::player(
+            Box::new(Impulse),
+            Box::new(Constant::new(room_size)),
+            Box::new(Constant::new(0.5)),
+            Box::new(Constant::new(mix)),
+            &consts,
+        );
+        let mut block = vec![Playable::zero(); 44100];
+        reverb.play_block(&State::initial(consts), &mut block);
+        block
+    }
+
+    fn energy(block: &[Playable]) -> f64 {
+        block.iter().map(|p| p.get_value().powi(2)).sum()
+    }
+
+    #[test]
+    fn test_dry() {
+        let played = play_impulse(0.5, 0.0);
+        assert_eq!(played[0], Playable::new(1.0));
+        assert_eq!(energy(&played[1..]), 0.0);
+    }
+
+    #[test]
+    fn test_tail() {
+        let small = play_impulse(0.1, 1.0);
+        let large = play_impulse(0.9, 1.0);
+        // Nothing until the shortest comb filter
+        assert_eq!(energy(&small[..1000]), 0.0);
+        assert!(energy(&small[1000..]) > 0.0);
+        // Larger rooms ring for longer
+        assert!(energy(&large[22050..]) > energy(&small[22050..]) * 10.0);
+        // Channels are decorrelated
+        assert!(large[5000].get_channel(0) != large[5000].get_channel(1));
+    }
+}
@@ -2,57 +2,546 @@ use core::spec::Spec;
 use core::spec::SpecField;
 use core::spec::SpecFieldDescription;
 use core::spec::SpecType;
+use core::tree::Tree;
 use core::Consts;
+use core::Pitch;
+use core::Playable;
+use core::Player;
+use core::State;
 use core::Time;
+use core::NUM_CHANNELS;
 use error::*;
-use inputs::Buffer;
-use players::PlayInput;
-use players::Speed;
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fs;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::ops::{Deref, Range};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 use hound;
 
-field_decl!(PATH, String, "Path of the .wav file to sample from");
-field_decl!(START, Time, "Start of the sample in the .wav file");
-field_decl!(DURATION, Time, "Duration of the sample in the .wav file");
+/// How many zero crossings of the sinc function are used either side of a
+/// frame
+const SINC_ZERO_CROSSINGS: f64 = 8.0;
+/// Root pitch of samples without a `smpl` chunk, i.e. c4
+const DEFAULT_ROOT_MIDI: f64 = 60.0;
+
+field_decl!(
+    PATH,
+    String,
+    "Path of the .wav file to sample from, relative to the sample-dir const"
+);
+field_decl!(
+    START,
+    Time,
+    "Start of the sample in the .wav file, in seconds or ticks"
+);
+field_decl!(
+    DURATION,
+    Time,
+    "Duration of the sample in the .wav file, in seconds or ticks"
+);
+field_decl!(
+    INTERPOLATION,
+    String,
+    "How to resample when the sample isn't played at the composition's rate, one of nearest, \
+     linear, cubic or sinc",
+    |_| "linear".to_string()
+);
+field_decl!(
+    REVERSE,
+    bool,
+    "Whether to play the sample backwards",
+    |_| false
+);
+field_decl!(
+    LOOP_START,
+    Option<Time>,
+    "Where the sample loops back to, from the start of the sample in seconds or ticks. Defaults \
+     to the loop in the file's smpl chunk, or the start of the sample",
+    |_| None
+);
+field_decl!(
+    LOOP_END,
+    Option<Time>,
+    "Where the sample loops from, from the start of the sample in seconds or ticks. Defaults to \
+     the loop in the file's smpl chunk, or the end of the sample",
+    |_| None
+);
+field_decl!(
+    PITCH,
+    Option<Pitch>,
+    "Pitch to play the sample at, defaults to the root pitch",
+    |_| None
+);
+field_decl!(
+    ROOT,
+    Option<Pitch>,
+    "Pitch the sample was recorded at. Defaults to the note in the file's smpl chunk, or c4",
+    |_| None
+);
+
+/// How to find the value of a sample between frames
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    /// Use the previous frame
+    Nearest,
+    /// Draw a line between the two closest frames
+    Linear,
+    /// Draw a Catmull-Rom spline through the four closest frames
+    Cubic,
+    /// Windowed sinc, which also filters out frequencies that are too high
+    /// when playing faster than the composition's rate
+    Sinc,
+}
+
+impl Interpolation {
+    fn from_string(string: &str) -> Result<Interpolation> {
+        match string {
+            "nearest" => Ok(Interpolation::Nearest),
+            "linear" => Ok(Interpolation::Linear),
+            "cubic" => Ok(Interpolation::Cubic),
+            "sinc" => Ok(Interpolation::Sinc),
+            interpolation => Err(ErrorKind::SpecError(format!(
+                "Unrecognized interpolation: {}",
+                interpolation
+            ))
+            .into()),
+        }
+    }
+}
+
+lazy_static! {
+    /// Decoded .wav files, shared between all samples so that they aren't read
+    /// again when reloading. Only the latest version of each file is kept
+    static ref CACHE: Mutex<HashMap<PathBuf, Arc<WavData>>> = Mutex::new(HashMap::new());
+}
+
+/// A whole decoded .wav file
+struct WavData {
+    modified: SystemTime,
+    /// Frames in the file, with the gain they have in the file
+    frames: Arc<Vec<Playable>>,
+    sample_hz: f64,
+    sampler: Option<SamplerChunk>,
+}
+
+impl WavData {
+    /// Get a decoded .wav file, only reading the file if it has changed since
+    /// it was last read
+    fn load(path: &Path) -> Result<Arc<WavData>> {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .chain_err(|| format!("Failed to get modified time of {}", path.display()))?;
+        if let Some(data) = CACHE.lock().unwrap().get(path) {
+            if data.modified == modified {
+                return Ok(data.clone());
+            }
+        }
+
+        let data = Arc::new(WavData::read(path, modified)?);
+        CACHE
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), data.clone());
+        Ok(data)
+    }
+
+    fn read(path: &Path, modified: SystemTime) -> Result<WavData> {
+        let mut reader = hound::WavReader::open(path)
+            .chain_err(|| format!("Failed to open .wav file: {}", path.display()))?;
+        let spec = reader.spec();
+        let num_channels = spec.channels as usize;
+
+        // Extract the samples, interleaved by channel, keeping their gain
+        // relative to full scale
+        let samples: Vec<f64> = match spec.sample_format {
+            hound::SampleFormat::Float => reader
+                .samples::<f32>()
+                .map(|r| r.map(f64::from))
+                .collect::<std::result::Result<_, _>>(),
+            hound::SampleFormat::Int => {
+                let full_scale = f64::from(1u32 << (spec.bits_per_sample - 1));
+                reader
+                    .samples::<i32>()
+                    .map(|r| r.map(|sample| f64::from(sample) / full_scale))
+                    .collect::<std::result::Result<_, _>>()
+            }
+        }
+        .chain_err(|| "Failed to read sample")?;
+
+        // Spread the channels in the file over the channels in a playable
+        let frames: Vec<Playable> = samples
+            .chunks(num_channels)
+            .map(|frame| {
+                let mut values = [0.0; NUM_CHANNELS];
+                for (i, value) in values.iter_mut().enumerate() {
+                    *value = frame[i % frame.len()];
+                }
+                Playable::from_channels(values)
+            })
+            .collect();
+
+        Ok(WavData {
+            modified,
+            frames: Arc::new(frames),
+            sample_hz: f64::from(spec.sample_rate),
+            sampler: read_sampler_chunk(path)?,
+        })
+    }
+}
+
+/// Frames from part of a .wav file, sharing the decoded file rather than
+/// copying it
+#[derive(Clone)]
+pub struct Frames {
+    all: Arc<Vec<Playable>>,
+    range: Range<usize>,
+}
+
+impl Frames {
+    /// Use all of `frames`
+    pub fn new(frames: Vec<Playable>) -> Frames {
+        Frames {
+            range: 0..frames.len(),
+            all: Arc::new(frames),
+        }
+    }
+}
+
+impl Deref for Frames {
+    type Target = [Playable];
+
+    fn deref(&self) -> &[Playable] {
+        &self.all[self.range.clone()]
+    }
+}
+
+/// The part of a .wav file used in a sample
+pub struct WavRegion {
+    /// Frames in the region, with the gain they have in the file
+    pub frames: Frames,
+    /// Sample rate of the file
+    pub sample_hz: f64,
+    /// The loop from the file's `smpl` chunk, in frames from the start of the
+    /// region
+    loop_frames: Option<(usize, usize)>,
+    /// The root pitch from the file's `smpl` chunk
+    root: Option<Pitch>,
+}
+
+impl WavRegion {
+    /// Get a region of a .wav file, only reading the file if it isn't cached
+    pub fn load(wav_path: &str, start: &Time, duration: &Time, consts: &Consts) -> Result<Self> {
+        WavRegion::load_seconds(
+            wav_path,
+            file_seconds(start, "start", consts)?,
+            file_seconds(duration, "duration", consts)?,
+            consts,
+        )
+    }
+
+    /// Get the whole of a .wav file
+    pub fn load_all(wav_path: &str, consts: &Consts) -> Result<Self> {
+        WavRegion::load_seconds(wav_path, 0.0, f64::INFINITY, consts)
+    }
+
+    fn load_seconds(
+        wav_path: &str,
+        start_seconds: f64,
+        duration_seconds: f64,
+        consts: &Consts,
+    ) -> Result<Self> {
+        let path = consts.sample_dir.join(wav_path);
+        let data = WavData::load(&path)?;
+        let start_frame = ((data.sample_hz * start_seconds) as usize).min(data.frames.len());
+        let end_frame = start_frame
+            .saturating_add((data.sample_hz * duration_seconds) as usize)
+            .min(data.frames.len());
+        if start_frame == end_frame {
+            bail!(ErrorKind::SpecError(format!(
+                "No samples read from .wav file: {}",
+                path.display()
+            )));
+        }
+
+        let loop_frames = data
+            .sampler
+            .as_ref()
+            .and_then(|sampler| sampler.loop_frames)
+            .map(|(loop_start, loop_end)| {
+                let clamp = |frame: usize| frame.max(start_frame).min(end_frame);
+                (
+                    clamp(loop_start) - start_frame,
+                    clamp(loop_end) - start_frame,
+                )
+            })
+            .filter(|(loop_start, loop_end)| loop_start < loop_end);
+        Ok(WavRegion {
+            frames: Frames {
+                all: data.frames.clone(),
+                range: start_frame..end_frame,
+            },
+            sample_hz: data.sample_hz,
+            loop_frames,
+            root: data.sampler.as_ref().map(|sampler| sampler.root.clone()),
+        })
+    }
+}
+
+/// Get how many seconds a time in a .wav file lasts
+///
+/// Times in beats or bars are rejected, as they would change with the tempo of
+/// the composition rather than following the file.
+fn file_seconds(time: &Time, name: &str, consts: &Consts) -> Result<f64> {
+    if time.follows_tempo() {
+        bail!(ErrorKind::SpecError(format!(
+            "The {} in a .wav file must be in seconds or ticks",
+            name
+        )));
+    }
+    Ok(time.to_seconds(consts))
+}
+
+/// Information from a `smpl` chunk in a .wav file
+struct SamplerChunk {
+    root: Pitch,
+    /// The first loop in the file, in frames from the start of the file
+    loop_frames: Option<(usize, usize)>,
+}
+
+/// Read the `smpl` chunk of a .wav file, if it has one, skipping over the
+/// other chunks
+fn read_sampler_chunk(path: &Path) -> Result<Option<SamplerChunk>> {
+    let file =
+        File::open(path).chain_err(|| format!("Failed to open .wav file: {}", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut riff_header = [0; 12];
+    if reader.read_exact(&mut riff_header).is_err()
+        || &riff_header[0..4] != b"RIFF"
+        || &riff_header[8..12] != b"WAVE"
+    {
+        return Ok(None);
+    }
+
+    let mut chunk_header = [0; 8];
+    while reader.read_exact(&mut chunk_header).is_ok() {
+        let chunk_size = u32::from_le_bytes([
+            chunk_header[4],
+            chunk_header[5],
+            chunk_header[6],
+            chunk_header[7],
+        ]);
+        if &chunk_header[0..4] == b"smpl" {
+            let mut data = vec![0; chunk_size as usize];
+            reader
+                .read_exact(&mut data)
+                .chain_err(|| format!("Failed to read smpl chunk in {}", path.display()))?;
+            let invalid =
+                || ErrorKind::SpecError(format!("Invalid smpl chunk in {}", path.display()));
+            let unity_note = read_u32(&data, 12).ok_or_else(invalid)?;
+            let pitch_fraction = read_u32(&data, 16).ok_or_else(invalid)?;
+            let num_loops = read_u32(&data, 28).ok_or_else(invalid)?;
+            // Loop ends are inclusive
+            let loop_frames = if num_loops > 0 {
+                let loop_start = read_u32(&data, 44).ok_or_else(invalid)?;
+                let loop_end = read_u32(&data, 48).ok_or_else(invalid)?;
+                Some((loop_start as usize, loop_end as usize + 1))
+            } else {
+                None
+            };
+            return Ok(Some(SamplerChunk {
+                root: Pitch::Midi(
+                    f64::from(unity_note) + f64::from(pitch_fraction) / 2_f64.powi(32),
+                ),
+                loop_frames,
+            }));
+        }
+        // Chunks are padded to an even number of bytes
+        reader
+            .seek(SeekFrom::Current(
+                i64::from(chunk_size) + i64::from(chunk_size % 2),
+            ))
+            .chain_err(|| format!("Failed to skip chunk in {}", path.display()))?;
+    }
+    Ok(None)
+}
+
+/// Read a little-endian `u32` at `offset`, if there are enough bytes
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
 
 /// Sample music from a .wav file
-pub struct Sample {}
+///
+/// Loops between the loop points when reaching the loop end, which defaults to
+/// the end of the sample.
+pub struct Sample {
+    /// Frames of the region being sampled
+    frames: Frames,
+    /// How many frames are played every tick
+    rate: f64,
+    loop_start: usize,
+    loop_end: usize,
+    interpolation: Interpolation,
+    reverse: bool,
+}
 
 impl Sample {
     #[allow(missing_docs)]
-    pub fn player(wav_path: String, start: Time, duration: Time, consts: &Consts) -> Result<Speed> {
-        let mut reader = hound::WavReader::open(&wav_path)
-            .chain_err(|| format!("Failed to open .wav file: {}", wav_path))?;
-        let start_seconds = start.to_seconds(consts);
-        let duration_seconds = duration.to_seconds(consts);
-        let sample_hz = reader.spec().sample_rate;
-        let num_channels = reader.spec().channels as usize;
-
-        // Skip to the part of the sample we want
-        reader
-            .seek((f64::from(sample_hz) * start_seconds) as u32)
-            .chain_err(|| format!("Failed to get seek to {} seconds", start_seconds))?;
+    pub fn player(
+        wav_path: String,
+        start: Time,
+        duration: Time,
+        consts: &Consts,
+    ) -> Result<Sample> {
+        let region = WavRegion::load(&wav_path, &start, &duration, consts)?;
+        let (loop_start, loop_end) = region.loop_frames.unwrap_or((0, region.frames.len()));
+        Sample::new(
+            region.frames.clone(),
+            region.sample_hz / consts.sample_hz,
+            (loop_start, loop_end),
+            Interpolation::Linear,
+            false,
+        )
+    }
+
+    /// Create a sample that plays `rate` frames every tick, looping between
+    /// frames `loop_frames`
+    pub fn new(
+        frames: Frames,
+        rate: f64,
+        loop_frames: (usize, usize),
+        interpolation: Interpolation,
+        reverse: bool,
+    ) -> Result<Sample> {
+        let (mut loop_start, mut loop_end) = loop_frames;
+        if loop_start >= loop_end || loop_end > frames.len() {
+            bail!(ErrorKind::SpecError(format!(
+                "Invalid loop from frame {} to {} in sample with {} frames",
+                loop_start,
+                loop_end,
+                frames.len()
+            )));
+        }
+        if rate <= 0.0 {
+            bail!(ErrorKind::SpecError(format!(
+                "Sample rate must be positive: {}",
+                rate
+            )));
+        }
+        if reverse {
+            let num_frames = frames.len();
+            loop_start = num_frames - loop_frames.1;
+            loop_end = num_frames - loop_frames.0;
+        }
+        Ok(Sample {
+            frames,
+            rate,
+            loop_start,
+            loop_end,
+            interpolation,
+            reverse,
+        })
+    }
+
+    /// Wrap a position past the loop end back into the loop
+    fn wrap(&self, position: f64) -> f64 {
+        let loop_end = self.loop_end as f64;
+        if position < loop_end {
+            position
+        } else {
+            let loop_start = self.loop_start as f64;
+            loop_start + (position - loop_start) % (loop_end - loop_start)
+        }
+    }
+
+    fn frame(&self, index: f64) -> Playable {
+        if index < 0.0 {
+            Playable::zero()
+        } else {
+            let index = self.wrap(index) as usize;
+            if self.reverse {
+                self.frames[self.frames.len() - 1 - index]
+            } else {
+                self.frames[index]
+            }
+        }
+    }
+
+    fn interpolate(&self, position: f64) -> Playable {
+        let index = position.floor();
+        let fraction = position - index;
+        match self.interpolation {
+            Interpolation::Nearest => self.frame(index),
+            Interpolation::Linear => {
+                self.frame(index) * (1.0 - fraction) + self.frame(index + 1.0) * fraction
+            }
+            Interpolation::Cubic => {
+                let p0 = self.frame(index - 1.0);
+                let p1 = self.frame(index);
+                let p2 = self.frame(index + 1.0);
+                let p3 = self.frame(index + 2.0);
+                let t = fraction;
+                p0 * (0.5 * (-t * t * t + 2.0 * t * t - t))
+                    + p1 * (0.5 * (3.0 * t * t * t - 5.0 * t * t + 2.0))
+                    + p2 * (0.5 * (-3.0 * t * t * t + 4.0 * t * t + t))
+                    + p3 * (0.5 * (t * t * t - t * t))
+            }
+            Interpolation::Sinc => {
+                // Lower the cutoff when playing faster to avoid aliasing
+                let cutoff = (1.0 / self.rate).min(1.0);
+                let half_width = SINC_ZERO_CROSSINGS / cutoff;
+                let mut playable = Playable::zero();
+                let mut total_weight = 0.0;
+                let mut frame_index = (position - half_width).ceil();
+                while frame_index <= position + half_width {
+                    let x = frame_index - position;
+                    let sinc = if x == 0.0 {
+                        1.0
+                    } else {
+                        (PI * cutoff * x).sin() / (PI * cutoff * x)
+                    };
+                    let window = 0.5 + 0.5 * (PI * x / half_width).cos();
+                    let weight = sinc * window;
+                    playable = playable + self.frame(frame_index) * weight;
+                    total_weight += weight;
+                    frame_index += 1.0;
+                }
+                playable * (1.0 / total_weight)
+            }
+        }
+    }
+}
+
+impl Player for Sample {
+    fn play(&mut self, state: &State) -> Playable {
+        let position = state.milli_tick as f64 / 1000.0 * self.rate;
+        self.interpolate(self.wrap(position))
+    }
 
-        // Extract the samples we need
-        let buffer: Vec<f64> = reader
-            .samples::<i32>()
-            // Only take the first channel
-            .enumerate()
-            .filter(|(i, _)| i % num_channels == 0)
-            .map(|(_, r)| r)
-            .take((f64::from(sample_hz) * duration_seconds) as usize)
-            .map(|r| r.map(f64::from))
-            .collect::<std::result::Result<_, _>>()
-            .chain_err(|| "Failed to read sample")?;
+    fn play_block(&mut self, state: &State, block: &mut [Playable]) {
+        let start = state.milli_tick as f64 / 1000.0;
+        for (i, playable) in block.iter_mut().enumerate() {
+            let position = (start + i as f64) * self.rate;
+            *playable = self.interpolate(self.wrap(position));
+        }
+    }
+}
 
-        Ok(Speed::player(
-            PlayInput::new(Buffer::new(buffer), consts),
-            f64::from(sample_hz) / consts.sample_hz,
-        )?)
+impl Tree for Sample {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
     }
 }
 
-impl SpecType<Speed> for Sample {
+impl SpecType for Sample {
     fn name() -> String {
         "sample".into()
     }
@@ -62,14 +551,293 @@ impl SpecType<Speed> for Sample {
             PATH.to_description(),
             START.to_description(),
             DURATION.to_description(),
+            INTERPOLATION.to_description(),
+            REVERSE.to_description(),
+            LOOP_START.to_description(),
+            LOOP_END.to_description(),
+            PITCH.to_description(),
+            ROOT.to_description(),
         ]
     }
 
-    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Speed> {
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Sample> {
         let wav_path = PATH.get(&mut spec, consts)?;
         let start = START.get(&mut spec, consts)?;
         let duration = DURATION.get(&mut spec, consts)?;
+        let interpolation = Interpolation::from_string(&INTERPOLATION.get(&mut spec, consts)?)?;
+        let reverse = REVERSE.get(&mut spec, consts)?;
+        let loop_start = LOOP_START.get(&mut spec, consts)?;
+        let loop_end = LOOP_END.get(&mut spec, consts)?;
+        let pitch = PITCH.get(&mut spec, consts)?;
+        let root = ROOT.get(&mut spec, consts)?;
         spec.ensure_all_used()?;
-        Sample::player(wav_path, start, duration, consts)
+
+        let region = WavRegion::load(&wav_path, &start, &duration, consts)?;
+        let (file_loop_start, file_loop_end) =
+            region.loop_frames.unwrap_or((0, region.frames.len()));
+        let to_frame = |time: Option<Time>, name, default| -> Result<usize> {
+            Ok(match time {
+                Some(time) => (file_seconds(&time, name, consts)? * region.sample_hz) as usize,
+                None => default,
+            })
+        };
+        let loop_frames = (
+            to_frame(loop_start, "loop start", file_loop_start)?,
+            to_frame(loop_end, "loop end", file_loop_end)?.min(region.frames.len()),
+        );
+
+        let root = root
+            .or_else(|| region.root.clone())
+            .unwrap_or(Pitch::Midi(DEFAULT_ROOT_MIDI));
+        let pitch_ratio = match pitch {
+            Some(pitch) => pitch.to_hz(consts) / root.to_hz(consts),
+            None => 1.0,
+        };
+        Sample::new(
+            region.frames.clone(),
+            region.sample_hz / consts.sample_hz * pitch_ratio,
+            loop_frames,
+            interpolation,
+            reverse,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use players::test_util::play;
+
+    use std::env;
+    use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom, Write};
+    use std::sync::Arc;
+
+    fn ramp(num_frames: usize) -> Frames {
+        Frames::new((0..num_frames).map(|i| Playable::new(i as f64)).collect())
+    }
+
+    /// Write a 16 bit mono .wav file, optionally with a `smpl` chunk with a
+    /// unity note and a loop
+    fn write_wav(name: &str, samples: &[i16], sampler: Option<(u32, u32, u32)>) -> String {
+        let path = env::temp_dir().join(name).to_str().unwrap().to_string();
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for sample in samples {
+            writer.write_sample(*sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        if let Some((unity_note, loop_start, loop_end)) = sampler {
+            let fields = [
+                36 + 24,
+                0,
+                0,
+                0,
+                unity_note,
+                0,
+                0,
+                0,
+                1,
+                0,
+                0,
+                0,
+                loop_start,
+                loop_end,
+                0,
+                0,
+            ];
+            let mut chunk: Vec<u8> = b"smpl".to_vec();
+            for field in &fields {
+                chunk.extend(&field.to_le_bytes());
+            }
+            let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+            let riff_size = file.seek(SeekFrom::End(0)).unwrap() as u32 + chunk.len() as u32 - 8;
+            file.write_all(&chunk).unwrap();
+            file.seek(SeekFrom::Start(4)).unwrap();
+            file.write_all(&riff_size.to_le_bytes()).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn test_gain() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let path = write_wav("compose-test-gain.wav", &[0, 8192, 16384, -16384], None);
+        let mut sample = Sample::player(path, Time::zero(), Time::Ticks(4), &consts).unwrap();
+        assert_eq!(
+            play(&mut sample, consts.clone(), 5),
+            vec![0.0, 0.25, 0.5, -0.5, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_cache() {
+        let mut consts = Consts::default().unwrap();
+        let path = write_wav("compose-test-cache.wav", &[0, 8192, 16384], None);
+        let data = WavData::load(Path::new(&path)).unwrap();
+        assert!(Arc::ptr_eq(
+            &data,
+            &WavData::load(Path::new(&path)).unwrap()
+        ));
+
+        // Regions are taken from the cached file, and paths are relative to the
+        // sample directory
+        consts.sample_dir = env::temp_dir();
+        let load = |start: usize, duration: usize, consts: &Consts| -> Result<Vec<f64>> {
+            let region = WavRegion::load(
+                "compose-test-cache.wav",
+                &Time::Ticks(start),
+                &Time::Ticks(duration),
+                consts,
+            )?;
+            Ok(region
+                .frames
+                .iter()
+                .map(|frame| frame.get_value())
+                .collect())
+        };
+        assert_eq!(load(0, 2, &consts).unwrap(), vec![0.0, 0.25]);
+        assert_eq!(load(1, 5, &consts).unwrap(), vec![0.25, 0.5]);
+        assert!(load(3, 1, &consts).is_err());
+        assert!(Arc::ptr_eq(
+            &data,
+            &WavData::load(Path::new(&path)).unwrap()
+        ));
+        // Regions share the frames of the cached file rather than copying them
+        let region = WavRegion::load(
+            "compose-test-cache.wav",
+            &Time::Ticks(1),
+            &Time::Ticks(1),
+            &consts,
+        )
+        .unwrap();
+        assert!(Arc::ptr_eq(&region.frames.all, &data.frames));
+
+        consts.sample_dir = PathBuf::from("does-not-exist");
+        assert!(load(0, 2, &consts).is_err());
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let sample =
+            |interpolation| Sample::new(ramp(8), 0.5, (0, 8), interpolation, false).unwrap();
+        assert_eq!(
+            play(&mut sample(Interpolation::Nearest), consts.clone(), 4),
+            vec![0.0, 0.0, 1.0, 1.0]
+        );
+        assert_eq!(
+            play(&mut sample(Interpolation::Linear), consts.clone(), 4),
+            vec![0.0, 0.5, 1.0, 1.5]
+        );
+        // Follows straight lines between frames
+        assert_eq!(
+            play(&mut sample(Interpolation::Cubic), consts.clone(), 6)[3],
+            1.5
+        );
+        // Keeps a constant signal constant
+        let mut sinc = Sample::new(
+            Frames::new(vec![Playable::new(0.5); 100]),
+            0.5,
+            (0, 100),
+            Interpolation::Sinc,
+            false,
+        )
+        .unwrap();
+        for value in &play(&mut sinc, consts.clone(), 100)[40..60] {
+            assert!((value - 0.5).abs() < 1e-9);
+        }
+        assert!(Interpolation::from_string("quadratic").is_err());
+    }
+
+    #[test]
+    fn test_loop_and_reverse() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut looped = Sample::new(ramp(5), 1.0, (2, 4), Interpolation::Linear, false).unwrap();
+        assert_eq!(
+            play(&mut looped, consts.clone(), 8),
+            vec![0.0, 1.0, 2.0, 3.0, 2.0, 3.0, 2.0, 3.0]
+        );
+        let mut reversed = Sample::new(ramp(5), 1.0, (0, 2), Interpolation::Linear, true).unwrap();
+        assert_eq!(
+            play(&mut reversed, consts.clone(), 8),
+            vec![4.0, 3.0, 2.0, 1.0, 0.0, 1.0, 0.0, 1.0]
+        );
+        assert!(Sample::new(ramp(5), 1.0, (2, 6), Interpolation::Linear, false).is_err());
+    }
+
+    #[test]
+    fn test_play_block() {
+        let consts = Arc::new(Consts::default().unwrap());
+        for interpolation in &[
+            Interpolation::Nearest,
+            Interpolation::Linear,
+            Interpolation::Cubic,
+            Interpolation::Sinc,
+        ] {
+            for reverse in &[false, true] {
+                let create =
+                    || Sample::new(ramp(50), 0.7, (10, 40), *interpolation, *reverse).unwrap();
+                let mut state = State::initial(consts.clone()).with_tick(3);
+                let mut sample = create();
+                let expected: Vec<Playable> = (0..100)
+                    .map(|_| {
+                        let playable = sample.play(&state);
+                        state.increment();
+                        playable
+                    })
+                    .collect();
+                let mut block = vec![Playable::zero(); 100];
+                create().play_block(&State::initial(consts.clone()).with_tick(3), &mut block);
+                assert_eq!(block, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sampler_chunk() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let samples: Vec<i16> = (0..8).map(|i| i * 1024).collect();
+        let path = write_wav("compose-test-smpl.wav", &samples, Some((57, 5, 6)));
+        let sampler = read_sampler_chunk(Path::new(&path)).unwrap().unwrap();
+        assert_eq!(sampler.root, Pitch::Midi(57.0));
+        assert_eq!(sampler.loop_frames, Some((5, 7)));
+
+        // Play an octave above the root, using the loop from the file
+        let spec = Spec::empty()
+            .with("path".into(), path)
+            .with("start".into(), "1 ticks".to_string())
+            .with("duration".into(), "7 ticks".to_string())
+            .with("pitch".into(), 69);
+        let mut sample = Sample::from_spec(spec, &consts).unwrap();
+        let played: Vec<f64> = play(&mut sample, consts.clone(), 6)
+            .into_iter()
+            .map(|value| value * 32768.0 / 1024.0)
+            .collect();
+        assert_eq!(played, vec![1.0, 3.0, 5.0, 5.0, 5.0, 5.0]);
+    }
+
+    #[test]
+    fn test_file_times() {
+        let consts = Consts::default().unwrap();
+        let path = write_wav("compose-test-times.wav", &[0; 100], None);
+        let sample = |start: &str, loop_start: &str| {
+            let spec = Spec::empty()
+                .with("path".into(), path.clone())
+                .with("start".into(), start.to_string())
+                .with("duration".into(), "50 ticks".to_string())
+                .with("loop-start".into(), loop_start.to_string());
+            Sample::from_spec(spec, &consts)
+        };
+        assert!(sample("0.0001 seconds", "10 ticks").is_ok());
+        // Times in the file can't follow the composition's tempo
+        assert!(sample("1 beats", "10 ticks").is_err());
+        assert!(sample("0 ticks", "1 bars").is_err());
     }
 }
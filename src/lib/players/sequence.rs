@@ -0,0 +1,336 @@
+use core::spec::FromValue;
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::spec::Value;
+use core::tree::Tree;
+use core::Consts;
+use core::Playable;
+use core::Player;
+use core::State;
+use core::Time;
+use error::*;
+
+field_decl!(
+    SECTIONS,
+    Vec<Section>,
+    "Sections to play, each with a `player`, a `duration`, and optionally a \
+     `start` which defaults to the end of the previous section"
+);
+field_decl!(
+    LOOP,
+    bool,
+    "If true, the arrangement repeats after the last section ends",
+    |_| false
+);
+field_decl!(
+    CROSSFADE,
+    Time,
+    "How long a section fades out for while the section after it fades in",
+    |_| Time::zero()
+);
+
+/// A player that is played for part of a sequence
+pub struct Section {
+    #[allow(missing_docs)]
+    pub player: Box<dyn Player>,
+    /// When the section starts, if not straight after the previous section
+    pub start: Option<Time>,
+    #[allow(missing_docs)]
+    pub duration: Time,
+}
+
+/// A section placed in the sequence
+struct PlacedSection {
+    player: Box<dyn Player>,
+    start_tick: usize,
+    duration_ticks: usize,
+    /// How long the section fades in from a section that ends as it starts,
+    /// zero if there isn't one
+    fade_in_ticks: usize,
+    /// How long the section fades out for after it ends
+    fade_out_ticks: usize,
+}
+
+/// Play children one after the other, each starting from its own beginning
+pub struct Sequence {
+    sections: Vec<PlacedSection>,
+    looping: bool,
+    /// Ticks until the end of the last section
+    length_ticks: usize,
+}
+
+impl Sequence {
+    #[allow(missing_docs)]
+    pub fn player(
+        sections: Vec<Section>,
+        looping: bool,
+        crossfade: &Time,
+        consts: &Consts,
+    ) -> Result<Sequence> {
+        let mut placed: Vec<PlacedSection> = Vec::with_capacity(sections.len());
+        for section in sections {
+            let start_tick = match section.start {
+                Some(start) => start.to_ticks(consts),
+                None => placed
+                    .last()
+                    .map(|previous| previous.start_tick + previous.duration_ticks)
+                    .unwrap_or(0),
+            };
+            placed.push(PlacedSection {
+                player: section.player,
+                start_tick,
+                duration_ticks: section.duration.ticks_after(start_tick, consts),
+                fade_in_ticks: 0,
+                fade_out_ticks: 0,
+            });
+        }
+
+        let length_ticks = placed
+            .iter()
+            .map(|section| section.start_tick + section.duration_ticks)
+            .max()
+            .unwrap_or(0);
+        if looping && length_ticks == 0 {
+            bail!(ErrorKind::SpecError(
+                "Can't loop a sequence with no length".into()
+            ));
+        }
+
+        let end_ticks: Vec<usize> = placed
+            .iter()
+            .map(|section| section.start_tick + section.duration_ticks)
+            .collect();
+        // Crossfades are resolved where they happen so that they follow the
+        // tempo
+        for section in &mut placed {
+            let end_tick = section.start_tick + section.duration_ticks;
+            section.fade_out_ticks = crossfade.ticks_after(end_tick, consts);
+            section.fade_in_ticks = if end_ticks.contains(&section.start_tick) {
+                crossfade.ticks_after(section.start_tick, consts)
+            } else if looping && section.start_tick == 0 {
+                // Fades in from the sections that end as the arrangement
+                // repeats
+                crossfade.ticks_after(length_ticks, consts)
+            } else {
+                0
+            };
+        }
+
+        Ok(Sequence {
+            sections: placed,
+            looping,
+            length_ticks,
+        })
+    }
+}
+
+impl Player for Sequence {
+    fn play(&mut self, state: &State) -> Playable {
+        let tick = state.tick();
+        let milli_tick_fraction = state.milli_tick % 1000;
+        // When looping, sections can still be fading out from the previous
+        // repeat
+        let (ticks, repeated) = if self.looping {
+            let loop_tick = tick % self.length_ticks;
+            let repeated = tick >= self.length_ticks;
+            let previous_tick = if repeated {
+                Some(loop_tick + self.length_ticks)
+            } else {
+                None
+            };
+            ([Some(loop_tick), previous_tick], repeated)
+        } else {
+            ([Some(tick), None], false)
+        };
+
+        let mut playable = Playable::zero();
+        for section in &mut self.sections {
+            let section_tick = ticks
+                .iter()
+                .filter_map(|tick| tick.and_then(|tick| tick.checked_sub(section.start_tick)))
+                .find(|tick| *tick < section.duration_ticks + section.fade_out_ticks);
+            let section_tick = match section_tick {
+                Some(section_tick) => section_tick,
+                None => continue,
+            };
+
+            let mut volume = 1.0;
+            // The very first section has nothing to fade in from
+            let first_play = section.start_tick == 0 && !repeated;
+            if !first_play && section_tick < section.fade_in_ticks {
+                volume *= section_tick as f64 / section.fade_in_ticks as f64;
+            }
+            if section_tick >= section.duration_ticks {
+                let fade_tick = section_tick - section.duration_ticks;
+                volume *= 1.0 - fade_tick as f64 / section.fade_out_ticks as f64;
+            }
+
+            let section_state = state.with_milli_tick(section_tick * 1000 + milli_tick_fraction);
+            playable = playable + section.player.play(&section_state) * volume;
+        }
+        playable
+    }
+}
+
+impl Tree for Sequence {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        self.sections
+            .iter()
+            .map(|section| section.player.to_tree())
+            .collect()
+    }
+}
+
+impl FromValue for Section {
+    fn name() -> String {
+        "section".into()
+    }
+
+    fn from_value(value: Value, consts: &Consts) -> Result<Section> {
+        let mut spec: Spec = value.into_type(consts)?;
+        let section = Section {
+            player: spec.consume("player", consts)?,
+            start: spec.consume_optional("start", consts)?,
+            duration: spec.consume("duration", consts)?,
+        };
+        spec.ensure_all_used()?;
+        Ok(section)
+    }
+}
+
+impl SpecType for Sequence {
+    fn name() -> String {
+        "sequence".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![
+            SECTIONS.to_description(),
+            LOOP.to_description(),
+            CROSSFADE.to_description(),
+        ]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Sequence> {
+        let sections = SECTIONS.get(&mut spec, consts)?;
+        let looping = LOOP.get(&mut spec, consts)?;
+        let crossfade = CROSSFADE.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+        Sequence::player(sections, looping, &crossfade, consts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::{TempoChange, TempoMap};
+    use players::test_util::{play, Counter};
+
+    use std::sync::Arc;
+
+    fn section(marker: f64, start: Option<usize>, duration: usize) -> Section {
+        Section {
+            player: Box::new(Counter(marker)),
+            start: start.map(Time::Ticks),
+            duration: Time::Ticks(duration),
+        }
+    }
+
+    #[test]
+    fn test_sections() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut sequence = Sequence::player(
+            vec![
+                section(100.0, None, 2),
+                section(200.0, None, 3),
+                section(300.0, Some(7), 1),
+            ],
+            false,
+            &Time::zero(),
+            &consts,
+        )
+        .unwrap();
+        assert_eq!(
+            play(&mut sequence, consts, 9),
+            vec![100.0, 101.0, 200.0, 201.0, 202.0, 0.0, 0.0, 300.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_loop() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut sequence = Sequence::player(
+            vec![section(100.0, None, 2), section(200.0, None, 1)],
+            true,
+            &Time::zero(),
+            &consts,
+        )
+        .unwrap();
+        assert_eq!(
+            play(&mut sequence, consts.clone(), 7),
+            vec![100.0, 101.0, 200.0, 100.0, 101.0, 200.0, 100.0]
+        );
+        assert!(Sequence::player(vec![], true, &Time::zero(), &consts).is_err());
+    }
+
+    #[test]
+    fn test_crossfade() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut sequence = Sequence::player(
+            vec![section(100.0, None, 4), section(200.0, None, 4)],
+            false,
+            &Time::Ticks(2),
+            &consts,
+        )
+        .unwrap();
+        let played = play(&mut sequence, consts, 11);
+        // No fade in at the start of the sequence
+        assert_eq!(played[0], 100.0);
+        // Fading from the first section to the second
+        assert_eq!(played[4], 104.0);
+        assert_eq!(played[5], 105.0 * 0.5 + 201.0 * 0.5);
+        assert_eq!(played[6], 202.0);
+        // Fading out the second section with nothing after it
+        assert_eq!(played[9], 205.0 * 0.5);
+        assert_eq!(played[10], 0.0);
+    }
+
+    #[test]
+    fn test_crossfade_tempo_changes() {
+        // Half speed from the second beat, where the sections cross
+        let mut consts = Consts::default().unwrap();
+        consts.tempo = TempoMap::new(
+            120.0,
+            vec![TempoChange {
+                beat: 1.0,
+                beats_per_minute: 60.0,
+                ramp: false,
+            }],
+        )
+        .unwrap();
+        let consts = Arc::new(consts);
+        let section = |marker| Section {
+            player: Box::new(Counter(marker)),
+            start: None,
+            duration: Time::Beats(1.0),
+        };
+        let mut sequence = Sequence::player(
+            vec![section(100.0), section(200.0)],
+            false,
+            &Time::Beats(0.5),
+            &consts,
+        )
+        .unwrap();
+        // The crossfade lasts half a beat at the new tempo, from tick 22050 to
+        // tick 44100
+        let played = play(&mut sequence, consts, 44101);
+        assert_eq!(played[33075], 33175.0 * 0.5 + 11225.0 * 0.5);
+        assert_eq!(played[44100], 22250.0);
+    }
+}
@@ -60,6 +60,19 @@ impl Player for Speed {
         let scaled_tick = self.scale(state.milli_tick);
         self.child.play(&state.with_milli_tick(scaled_tick))
     }
+
+    fn play_block(&mut self, state: &State, block: &mut [Playable]) {
+        if self.scale_numerator == self.scale_denominator {
+            self.child.play_block(state, block);
+            return;
+        }
+        // Reuse one state for the child, rather than creating one per tick
+        let mut child_state = state.clone();
+        for (i, playable) in block.iter_mut().enumerate() {
+            child_state.milli_tick = self.scale(state.milli_tick + i * 1000);
+            *playable = self.child.play(&child_state);
+        }
+    }
 }
 
 impl Tree for Speed {
@@ -148,7 +161,7 @@ mod test {
             let played0 = speed0.play(&state).get_value();
             let played1 = speed1.play(&state).get_value();
             let diff = dbg!((played0 - played1).abs());
-            assert!(diff <= 1);
+            assert!(diff <= 1.0);
             state.increment();
             if state.tick() > 100000 {
                 break;
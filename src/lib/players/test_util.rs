@@ -0,0 +1,65 @@
+//! Players and helpers shared by the players' tests
+
+use core::tree::Tree;
+use core::Consts;
+use core::Playable;
+use core::Player;
+use core::State;
+
+use std::sync::Arc;
+
+/// Plays one on every tick
+pub struct Ones;
+
+impl Player for Ones {
+    fn play(&mut self, _state: &State) -> Playable {
+        Playable::new(1.0)
+    }
+}
+
+impl Tree for Ones {
+    fn to_tree(&self) -> &dyn Tree {
+        self
+    }
+}
+
+/// Plays one at tick zero, and nothing otherwise
+pub struct Impulse;
+
+impl Player for Impulse {
+    fn play(&mut self, state: &State) -> Playable {
+        if state.tick() == 0 {
+            Playable::new(1.0)
+        } else {
+            Playable::zero()
+        }
+    }
+}
+
+impl Tree for Impulse {
+    fn to_tree(&self) -> &dyn Tree {
+        self
+    }
+}
+
+/// Plays its own tick, offset by a marker
+pub struct Counter(pub f64);
+
+impl Player for Counter {
+    fn play(&mut self, state: &State) -> Playable {
+        Playable::new(self.0 + state.tick() as f64)
+    }
+}
+
+impl Tree for Counter {
+    fn to_tree(&self) -> &dyn Tree {
+        self
+    }
+}
+
+/// Play a block from the start, and get the value of each tick
+pub fn play(player: &mut dyn Player, consts: Arc<Consts>, num_ticks: usize) -> Vec<f64> {
+    let mut block = vec![Playable::zero(); num_ticks];
+    player.play_block(&State::initial(consts), &mut block);
+    block.into_iter().map(Playable::get_value).collect()
+}
@@ -17,12 +17,18 @@ field_decl!(INPUT, Box<dyn Input>, "Controls the volume level");
 pub struct Volume {
     child: Box<dyn Player>,
     input: Box<dyn Input>,
+    /// Reused for the input when playing blocks
+    input_block: Vec<f64>,
 }
 
 impl Volume {
     #[allow(missing_docs)]
     pub fn player(child: Box<dyn Player>, input: Box<dyn Input>) -> Volume {
-        Volume { child, input }
+        Volume {
+            child,
+            input,
+            input_block: Vec::new(),
+        }
     }
 }
 
@@ -30,6 +36,15 @@ impl Player for Volume {
     fn play(&mut self, state: &State) -> Playable {
         self.child.play(state) * self.input.get(state)
     }
+
+    fn play_block(&mut self, state: &State, block: &mut [Playable]) {
+        self.child.play_block(state, block);
+        self.input_block.resize(block.len(), 0.0);
+        self.input.get_block(state, &mut self.input_block);
+        for (playable, volume) in block.iter_mut().zip(&self.input_block) {
+            *playable = *playable * *volume;
+        }
+    }
 }
 
 impl Tree for Volume {
@@ -1,33 +1,165 @@
+use core::spec::FromValue;
 use core::spec::Spec;
 use core::spec::SpecField;
 use core::spec::SpecFieldDescription;
 use core::spec::SpecType;
+use core::spec::Value;
+use core::tree::Tree;
 use core::Consts;
 use core::Input;
+use core::Pitch;
+use core::Playable;
+use core::Player;
+use core::State;
 use error::*;
+use inputs::Constant;
 use inputs::Function;
-use players::PlayInput;
-use players::Speed;
+use players::Oscillator;
+use players::WavetableShape;
 
 field_decl!(
     FN,
-    Box<dyn Input>,
-    "The function that defines the wave shape",
-    |_| Box::new(Function::default()) as Box<dyn Input>
+    WaveShape,
+    "The function that defines the wave shape, or a band-limited oscillator",
+    |_| WaveShape::Input(Box::new(Function::default()))
+);
+field_decl!(
+    FREQUENCY,
+    Value,
+    "Frequency of the wave, as Hz, a note name (e.g. c#5), a MIDI number, or an input giving Hz"
 );
-field_decl!(FREQUENCY, f64, "Frequency of the wave");
+
+/// The shape of a single cycle of a wave
+///
+/// In specs, oscillators are given by their shape's name or a spec named
+/// `oscillator`, anything else is an input.
+pub enum WaveShape {
+    /// Called with one second of ticks for every cycle of the wave
+    Input(Box<dyn Input>),
+    #[allow(missing_docs)]
+    Oscillator(Oscillator),
+    #[allow(missing_docs)]
+    Wavetable(WavetableShape),
+}
 
 /// Play a wave from a wave function
-pub struct Wave {}
+///
+/// The wave's phase is accumulated every tick, so that the frequency can
+/// change while playing.
+pub struct Wave {
+    shape: WaveShape,
+    frequency: Box<dyn Input>,
+    /// How far through the current cycle the wave is, in [0, 1)
+    phase: f64,
+    /// The milli tick the phase was last accumulated to
+    last_milli_tick: Option<usize>,
+    /// Reused for the frequency when playing blocks
+    frequency_block: Vec<f64>,
+}
 
 impl Wave {
     #[allow(missing_docs)]
-    pub fn new(input: Box<dyn Input>, frequency: f64, consts: &Consts) -> Result<Speed> {
-        Speed::player(PlayInput::new(input, consts), frequency)
+    pub fn new(input: Box<dyn Input>, frequency: f64) -> Wave {
+        Wave::with_shape(WaveShape::Input(input), Box::new(Constant::new(frequency)))
+    }
+
+    /// Create a wave with a frequency in Hz that can change over time
+    pub fn with_shape(shape: WaveShape, frequency: Box<dyn Input>) -> Wave {
+        Wave {
+            shape,
+            frequency,
+            phase: 0.0,
+            last_milli_tick: None,
+            frequency_block: Vec::new(),
+        }
+    }
+
+    /// Play the wave with its phase moved forwards by `offset` cycles
+    pub fn play_with_phase_offset(&mut self, state: &State, offset: f64) -> Playable {
+        let frequency = self.frequency.get(state);
+        self.accumulate_phase(state, frequency);
+        let value = self.shape_value(state, offset, frequency);
+        Playable::new(value * state.consts.loudness_factor)
+    }
+
+    /// Move the phase on to the state's milli tick
+    fn accumulate_phase(&mut self, state: &State, frequency: f64) {
+        let sample_hz = state.consts.sample_hz;
+        match self.last_milli_tick {
+            Some(last_milli_tick) if last_milli_tick <= state.milli_tick => {
+                let ticks = (state.milli_tick - last_milli_tick) as f64 / 1000.0;
+                self.phase = (self.phase + frequency * ticks / sample_hz).fract();
+            }
+            // Start again from the state's tick when jumping backwards, so that
+            // the wave repeats when its tick repeats
+            _ => {
+                self.phase = (frequency * state.milli_tick as f64 / 1000.0 / sample_hz).fract();
+            }
+        }
+        self.last_milli_tick = Some(state.milli_tick);
+    }
+
+    /// Get the value of the shape at the current phase moved forwards by
+    /// `offset` cycles
+    fn shape_value(&mut self, state: &State, offset: f64, frequency: f64) -> f64 {
+        let sample_hz = state.consts.sample_hz;
+        let phase = (self.phase + offset).rem_euclid(1.0);
+        match &mut self.shape {
+            WaveShape::Input(function) => {
+                let function_milli_tick = (phase * sample_hz * 1000.0) as usize;
+                function.get(&state.with_milli_tick(function_milli_tick))
+            }
+            WaveShape::Oscillator(oscillator) => {
+                oscillator.get(state, phase, frequency / sample_hz)
+            }
+            WaveShape::Wavetable(wavetable) => wavetable.get(state, phase, frequency / sample_hz),
+        }
+    }
+
+    /// Get the frequency in Hz from a spec field, which can be an input or a
+    /// pitch
+    pub fn frequency_input(frequency: Value, consts: &Consts) -> Result<Box<dyn Input>> {
+        match frequency {
+            Value::Spec(_) => frequency.into_type(consts),
+            frequency => {
+                let pitch: Pitch = frequency.into_type(consts)?;
+                Ok(Box::new(Constant::new(pitch.to_hz(consts))))
+            }
+        }
+    }
+}
+
+impl Player for Wave {
+    fn play(&mut self, state: &State) -> Playable {
+        self.play_with_phase_offset(state, 0.0)
+    }
+
+    fn play_block(&mut self, state: &State, block: &mut [Playable]) {
+        let mut frequency_block = std::mem::take(&mut self.frequency_block);
+        frequency_block.resize(block.len(), 0.0);
+        self.frequency.get_block(state, &mut frequency_block);
+        let mut state = state.clone();
+        for (playable, frequency) in block.iter_mut().zip(&frequency_block) {
+            self.accumulate_phase(&state, *frequency);
+            let value = self.shape_value(&state, 0.0, *frequency);
+            *playable = Playable::new(value * state.consts.loudness_factor);
+            state.increment();
+        }
+        self.frequency_block = frequency_block;
+    }
+}
+
+impl Tree for Wave {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![self.shape.to_tree(), self.frequency.to_tree()]
     }
 }
 
-impl SpecType<Speed> for Wave {
+impl SpecType for Wave {
     fn name() -> String {
         "wave".into()
     }
@@ -36,10 +168,169 @@ impl SpecType<Speed> for Wave {
         vec![FN.to_description(), FREQUENCY.to_description()]
     }
 
-    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Speed> {
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Wave> {
         let function = FN.get(&mut spec, consts)?;
         let frequency = FREQUENCY.get(&mut spec, consts)?;
         spec.ensure_all_used()?;
-        Wave::new(function, frequency, consts)
+        Ok(Wave::with_shape(
+            function,
+            Wave::frequency_input(frequency, consts)?,
+        ))
+    }
+}
+
+impl WaveShape {
+    fn to_tree(&self) -> &dyn Tree {
+        match self {
+            WaveShape::Input(function) => function.to_tree(),
+            WaveShape::Oscillator(oscillator) => oscillator.to_tree(),
+            WaveShape::Wavetable(wavetable) => wavetable.to_tree(),
+        }
+    }
+}
+
+impl FromValue for WaveShape {
+    fn name() -> String {
+        "wave-shape".into()
+    }
+
+    fn from_value(value: Value, consts: &Consts) -> Result<WaveShape> {
+        let is_oscillator = match &value {
+            Value::Str(_) => true,
+            Value::Spec(spec) => match spec.get::<String>("name") {
+                Ok(name) => name == "oscillator",
+                Err(_) => false,
+            },
+            _ => false,
+        };
+        if is_oscillator {
+            Ok(WaveShape::Oscillator(value.into_type(consts)?))
+        } else {
+            Ok(WaveShape::Input(value.into_type(consts)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use players::test_util::play;
+    use players::Speed;
+
+    use std::f64::consts::PI;
+    use std::sync::Arc;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_constant_frequency() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let mut wave = Wave::new(Box::new(Function::default()), 441.0);
+        let played = play(&mut wave, consts.clone(), 200);
+        for (tick, value) in played.iter().enumerate() {
+            let expected = (2.0 * PI * tick as f64 / 100.0).sin() * consts.loudness_factor;
+            assert_close(*value, expected);
+        }
+
+        // Playing the same tick again doesn't move the wave on
+        let state = State::initial(consts.clone()).with_tick(25);
+        let value = wave.play(&state);
+        assert_eq!(wave.play(&state), value);
+    }
+
+    #[test]
+    fn test_speed() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let wave = Wave::new(Box::new(Function::default()), 441.0);
+        let mut wave_double = Wave::new(Box::new(Function::default()), 882.0);
+        let mut sped_up = Speed::player(Box::new(wave), 2.0).unwrap();
+        let played = play(&mut sped_up, consts.clone(), 100);
+        let expected = play(&mut wave_double, consts, 100);
+        for (value, expected) in played.iter().zip(&expected) {
+            assert_close(*value, *expected);
+        }
+    }
+
+    #[test]
+    fn test_play_block() {
+        let consts = Arc::new(Consts::default().unwrap());
+        // A rising frequency, with the default sine function and an oscillator
+        let create_wave = |oscillator: bool| {
+            let shape = if oscillator {
+                WaveShape::from_value(Value::Str("saw".into()), &consts).unwrap()
+            } else {
+                WaveShape::Input(Box::new(Function::default()))
+            };
+            let frequency = Function::new(Box::new(|x| 440.0 + 1000.0 * x));
+            Wave::with_shape(shape, Box::new(frequency))
+        };
+        for oscillator in &[false, true] {
+            let mut block_wave = create_wave(*oscillator);
+            let mut wave = create_wave(*oscillator);
+            let mut state = State::initial(consts.clone());
+            let mut block = vec![Playable::zero(); 100];
+            for _ in 0..10 {
+                block_wave.play_block(&state, &mut block);
+                for playable in &block {
+                    assert_eq!(*playable, wave.play(&state));
+                    state.increment();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_frequency_input() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let frequency = Value::Spec(
+            Spec::empty()
+                .with("name".into(), "constant".to_string())
+                .with("value".into(), 441.0),
+        );
+        let mut wave = Wave::with_shape(
+            WaveShape::Input(Box::new(Function::default())),
+            Wave::frequency_input(frequency, &consts).unwrap(),
+        );
+        let mut note = Wave::with_shape(
+            WaveShape::Input(Box::new(Function::default())),
+            Wave::frequency_input(Value::Str("a4".into()), &consts).unwrap(),
+        );
+        assert_close(
+            play(&mut wave, consts.clone(), 26)[25],
+            consts.loudness_factor,
+        );
+        assert_close(
+            play(&mut note, consts.clone(), 101)[100],
+            (2.0 * PI * 440.0 / 441.0).sin() * consts.loudness_factor,
+        );
+    }
+
+    #[test]
+    fn test_oscillator() {
+        let consts = Arc::new(Consts::default().unwrap());
+        let shape = |value: Value| WaveShape::from_value(value, &consts).unwrap();
+        let mut saw = Wave::with_shape(
+            shape(Value::Str("saw".into())),
+            Box::new(Constant::new(4410.0)),
+        );
+        let played = play(&mut saw, consts.clone(), 10);
+        assert_close(played[0], 0.0);
+        assert_close(played[5], 0.0);
+        assert_close(played[9], 0.8 * consts.loudness_factor);
+
+        let spec = Spec::empty()
+            .with("name".into(), "oscillator".to_string())
+            .with("shape".into(), "pulse".to_string());
+        match shape(Value::Spec(spec)) {
+            WaveShape::Oscillator(_) => {}
+            _ => panic!("Expected oscillator"),
+        }
+        let spec = Spec::empty().with("name".into(), "function".to_string());
+        match shape(Value::Spec(spec)) {
+            WaveShape::Input(_) => {}
+            _ => panic!("Expected input"),
+        }
     }
 }
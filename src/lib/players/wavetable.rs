@@ -0,0 +1,337 @@
+use core::spec::Spec;
+use core::spec::SpecField;
+use core::spec::SpecFieldDescription;
+use core::spec::SpecType;
+use core::spec::Value;
+use core::tree::Tree;
+use core::Consts;
+use core::Input;
+use core::Playable;
+use core::Player;
+use core::State;
+use error::*;
+use inputs::Constant;
+use players::sample::WavRegion;
+use players::{Wave, WaveShape};
+
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::FFTplanner;
+
+/// Size of the table with the most harmonics
+const TABLE_SIZE: usize = 2048;
+/// Most harmonics a table can have
+const MAX_HARMONICS: usize = TABLE_SIZE / 2;
+/// Size of the smallest table, so that tables with few harmonics are still
+/// smooth when interpolating
+const MIN_TABLE_SIZE: usize = 256;
+/// How many mip-mapped tables each frame has, each with half the harmonics of
+/// the last
+const NUM_LEVELS: usize = 11;
+
+field_decl!(
+    PATH,
+    Option<String>,
+    "Path of a .wav file of frames one after another, relative to the sample-dir const",
+    |_| None
+);
+field_decl!(
+    FRAME_SIZE,
+    i32,
+    "How many samples each frame in the .wav file has",
+    |_| TABLE_SIZE as i32
+);
+field_decl!(
+    HARMONICS,
+    Option<Vec<f64>>,
+    "Amplitudes of each harmonic, starting with the fundamental, used instead of a .wav file",
+    |_| None
+);
+field_decl!(
+    POSITION,
+    Box<dyn Input>,
+    "Which frame to play, from 0 at the first to 1 at the last, morphing in between",
+    |_| Box::new(Constant::new(0.0)) as Box<dyn Input>
+);
+field_decl!(
+    FREQUENCY,
+    Value,
+    "Frequency of the wave, as Hz, a note name (e.g. c#5), a MIDI number, or an input giving Hz"
+);
+
+/// Frames of single cycles of a wave, each with tables of fewer harmonics for
+/// higher notes so that they don't alias
+pub struct WavetableShape {
+    /// Tables for every frame, from the most harmonics to the fewest
+    frames: Vec<Vec<Vec<f64>>>,
+    position: Box<dyn Input>,
+}
+
+impl WavetableShape {
+    /// Create from frames of single cycles, with any number of samples
+    pub fn from_frames(frames: &[Vec<f64>], position: Box<dyn Input>) -> Result<WavetableShape> {
+        if frames.is_empty() || frames.iter().any(|frame| frame.len() < 2) {
+            bail!(ErrorKind::SpecError(
+                "Wavetable frames need at least two samples".into()
+            ));
+        }
+        let mut planner = FFTplanner::new(false);
+        let spectra = frames
+            .iter()
+            .map(|frame| {
+                let fft = planner.plan_fft(frame.len());
+                let mut input: Vec<Complex<f64>> =
+                    frame.iter().map(|x| Complex::new(*x, 0.0)).collect();
+                let mut output = vec![Complex::zero(); frame.len()];
+                fft.process(&mut input, &mut output);
+                // Skip the DC offset, and the Nyquist frequency which has no
+                // phase
+                output[1..=(frame.len() - 1) / 2]
+                    .iter()
+                    .map(|x| x / frame.len() as f64)
+                    .collect()
+            })
+            .collect::<Vec<Vec<Complex<f64>>>>();
+        Ok(WavetableShape::from_spectra(&spectra, position))
+    }
+
+    /// Create a single frame from the amplitudes of sine harmonics, normalised
+    /// so that it peaks at 1
+    pub fn from_harmonics(harmonics: &[f64], position: Box<dyn Input>) -> Result<WavetableShape> {
+        if harmonics.iter().all(|amplitude| *amplitude == 0.0) {
+            bail!(ErrorKind::SpecError(
+                "Wavetable needs a harmonic that isn't zero".into()
+            ));
+        }
+        // A sine of amplitude a has a coefficient of -ia/2
+        let spectrum: Vec<Complex<f64>> = harmonics
+            .iter()
+            .map(|amplitude| Complex::new(0.0, -amplitude / 2.0))
+            .collect();
+        let mut shape = WavetableShape::from_spectra(&[spectrum], position);
+        let peak = shape.frames[0][0]
+            .iter()
+            .fold(0.0, |peak: f64, value| peak.max(value.abs()));
+        for table in &mut shape.frames[0] {
+            for value in table.iter_mut() {
+                *value /= peak;
+            }
+        }
+        Ok(shape)
+    }
+
+    /// Create from the coefficients of each frame's harmonics, starting with
+    /// the fundamental
+    fn from_spectra(spectra: &[Vec<Complex<f64>>], position: Box<dyn Input>) -> WavetableShape {
+        let mut planner = FFTplanner::new(true);
+        let frames = spectra
+            .iter()
+            .map(|spectrum| {
+                (0..NUM_LEVELS)
+                    .map(|level| {
+                        let size = (TABLE_SIZE >> level).max(MIN_TABLE_SIZE);
+                        let num_harmonics = (MAX_HARMONICS >> level)
+                            .min(size / 2 - 1)
+                            .min(spectrum.len());
+                        let mut input = vec![Complex::zero(); size];
+                        for (harmonic, coefficient) in spectrum[..num_harmonics].iter().enumerate()
+                        {
+                            input[harmonic + 1] = *coefficient;
+                            input[size - harmonic - 1] = coefficient.conj();
+                        }
+                        let mut output = vec![Complex::zero(); size];
+                        planner.plan_fft(size).process(&mut input, &mut output);
+                        output.into_iter().map(|x| x.re).collect()
+                    })
+                    .collect()
+            })
+            .collect();
+        WavetableShape { frames, position }
+    }
+
+    /// Get the value of the wavetable at `phase` in [0, 1), where the phase
+    /// moves by `phase_step` every tick
+    pub fn get(&mut self, state: &State, phase: f64, phase_step: f64) -> f64 {
+        // Use the most harmonics that stay under the Nyquist frequency
+        let max_harmonics = 0.5 / phase_step.abs();
+        let level = (0..NUM_LEVELS)
+            .find(|level| (MAX_HARMONICS >> level) as f64 <= max_harmonics)
+            .unwrap_or(NUM_LEVELS - 1);
+
+        let position = self.position.get(state).clamp(0.0, 1.0);
+        let frame_position = position * (self.frames.len() - 1) as f64;
+        let frame = frame_position.floor() as usize;
+        let fraction = frame_position - frame as f64;
+        let value = Self::table_value(&self.frames[frame][level], phase);
+        if fraction > 0.0 {
+            let next_value = Self::table_value(&self.frames[frame + 1][level], phase);
+            value * (1.0 - fraction) + next_value * fraction
+        } else {
+            value
+        }
+    }
+
+    /// Linearly interpolate a table at `phase`
+    fn table_value(table: &[f64], phase: f64) -> f64 {
+        let index = phase * table.len() as f64;
+        let fraction = index - index.floor();
+        let index = index as usize % table.len();
+        table[index] * (1.0 - fraction) + table[(index + 1) % table.len()] * fraction
+    }
+}
+
+impl Tree for WavetableShape {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![self.position.to_tree()]
+    }
+}
+
+/// Play a wavetable loaded from a .wav file or built from harmonics
+pub struct Wavetable {
+    wave: Wave,
+}
+
+impl Wavetable {
+    #[allow(missing_docs)]
+    pub fn player(shape: WavetableShape, frequency: Box<dyn Input>) -> Wavetable {
+        Wavetable {
+            wave: Wave::with_shape(WaveShape::Wavetable(shape), frequency),
+        }
+    }
+}
+
+impl Player for Wavetable {
+    fn play(&mut self, state: &State) -> Playable {
+        self.wave.play(state)
+    }
+}
+
+impl Tree for Wavetable {
+    fn to_tree(&self) -> &dyn Tree {
+        self as &dyn Tree
+    }
+
+    fn get_children(&self) -> Vec<&dyn Tree> {
+        vec![self.wave.to_tree()]
+    }
+}
+
+impl SpecType for Wavetable {
+    fn name() -> String {
+        "wavetable".into()
+    }
+
+    fn field_descriptions() -> Vec<SpecFieldDescription> {
+        vec![
+            PATH.to_description(),
+            FRAME_SIZE.to_description(),
+            HARMONICS.to_description(),
+            POSITION.to_description(),
+            FREQUENCY.to_description(),
+        ]
+    }
+
+    fn from_spec(mut spec: Spec, consts: &Consts) -> Result<Wavetable> {
+        let wav_path = PATH.get(&mut spec, consts)?;
+        let frame_size = FRAME_SIZE.get(&mut spec, consts)?;
+        let harmonics = HARMONICS.get(&mut spec, consts)?;
+        let position = POSITION.get(&mut spec, consts)?;
+        let frequency = FREQUENCY.get(&mut spec, consts)?;
+        spec.ensure_all_used()?;
+
+        let shape = match (wav_path, harmonics) {
+            (Some(wav_path), None) => {
+                if frame_size < 2 {
+                    bail!(ErrorKind::SpecError(format!(
+                        "Wavetable frame size must be at least 2, got {}",
+                        frame_size
+                    )));
+                }
+                let region = WavRegion::load_all(&wav_path, consts)?;
+                let samples: Vec<f64> = region
+                    .frames
+                    .iter()
+                    .map(|playable| playable.get_value())
+                    .collect();
+                // A file shorter than a frame is a single cycle
+                let frame_size = (frame_size as usize).min(samples.len());
+                let frames: Vec<Vec<f64>> = samples
+                    .chunks_exact(frame_size)
+                    .map(|frame| frame.to_vec())
+                    .collect();
+                WavetableShape::from_frames(&frames, position)
+                    .chain_err(|| format!("Failed to create wavetable from {}", wav_path))?
+            }
+            (None, Some(harmonics)) => WavetableShape::from_harmonics(&harmonics, position)?,
+            _ => bail!(ErrorKind::SpecError(
+                "Wavetable needs exactly one of path or harmonics".into()
+            )),
+        };
+        Ok(Wavetable::player(
+            shape,
+            Wave::frequency_input(frequency, consts)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::f64::consts::PI;
+    use std::sync::Arc;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+    }
+
+    fn sine(num_samples: usize, amplitude: f64) -> Vec<f64> {
+        (0..num_samples)
+            .map(|i| amplitude * (2.0 * PI * i as f64 / num_samples as f64).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_harmonics() {
+        let state = State::initial(Arc::new(Consts::default().unwrap()));
+        let create = |harmonics: &[f64]| {
+            WavetableShape::from_harmonics(harmonics, Box::new(Constant::new(0.0))).unwrap()
+        };
+        let mut sine = create(&[2.0]);
+        assert_close(sine.get(&state, 0.25, 1.0 / 44100.0), 1.0);
+        assert_close(sine.get(&state, 0.5, 1.0 / 44100.0), 0.0);
+
+        let mut shape = create(&[1.0, 0.0, 1.0]);
+        let expected = |phase: f64| (2.0 * PI * phase).sin() + (6.0 * PI * phase).sin();
+        let peak = (0..TABLE_SIZE)
+            .map(|i| expected(i as f64 / TABLE_SIZE as f64))
+            .fold(0.0, f64::max);
+        for phase in &[0.1, 0.3, 0.7] {
+            // Low notes have every harmonic
+            let value = shape.get(&state, *phase, 100.0 / 44100.0);
+            assert!((value - expected(*phase) / peak).abs() < 1e-3);
+            // High notes drop harmonics over the Nyquist frequency
+            let value = shape.get(&state, *phase, 10000.0 / 44100.0);
+            assert!((value - (2.0 * PI * phase).sin() / peak).abs() < 1e-3);
+        }
+        assert!(WavetableShape::from_harmonics(&[0.0], Box::new(Constant::new(0.0))).is_err());
+    }
+
+    #[test]
+    fn test_frames() {
+        let state = State::initial(Arc::new(Consts::default().unwrap()));
+        let frames = vec![sine(64, 1.0), sine(100, -0.5)];
+        let mut shape = WavetableShape::from_frames(&frames, Box::new(Constant::new(0.0))).unwrap();
+        let step = 1.0 / 44100.0;
+        assert!((shape.get(&state, 0.25, step) - 1.0).abs() < 1e-3);
+        shape.position = Box::new(Constant::new(1.0));
+        assert!((shape.get(&state, 0.25, step) + 0.5).abs() < 1e-3);
+        shape.position = Box::new(Constant::new(0.5));
+        assert!((shape.get(&state, 0.25, step) - 0.25).abs() < 1e-3);
+        assert!(WavetableShape::from_frames(&[], Box::new(Constant::new(0.0))).is_err());
+    }
+}
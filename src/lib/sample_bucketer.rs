@@ -8,8 +8,8 @@ const PADDING_PERC: f64 = 0.1;
 /// Takes samples, and puts them into lower resolution buckets, only storing the
 /// minimum and the maximum values
 pub struct SampleBucketer {
-    bucket_min_max: Vec<(i32, i32)>,
-    all_min_max: (i32, i32),
+    bucket_min_max: Vec<(f64, f64)>,
+    all_min_max: (f64, f64),
     num_samples: usize,
     last_bucket_index: usize,
 }
@@ -19,15 +19,15 @@ impl SampleBucketer {
     /// of buckets to put them in
     pub fn new(num_samples: usize, num_buckets: usize) -> SampleBucketer {
         SampleBucketer {
-            bucket_min_max: vec![(0, 0); num_buckets],
-            all_min_max: (0, 0),
+            bucket_min_max: vec![(0.0, 0.0); num_buckets],
+            all_min_max: (0.0, 0.0),
             num_samples,
             last_bucket_index: 0,
         }
     }
 
     /// Adds a sample to the bucketer
-    pub fn add_sample(&mut self, sample: i32, index: usize) {
+    pub fn add_sample(&mut self, sample: f64, index: usize) {
         let bucket_index = ((index % self.num_samples) as f64
             * (self.bucket_min_max.len() as f64 / self.num_samples as f64))
             as usize;
@@ -49,7 +49,7 @@ impl SampleBucketer {
     }
 
     /// Create an iterator over the mins and maxes of the buckets
-    pub fn iter(&self) -> impl Iterator<Item = &(i32, i32)> + '_ {
+    pub fn iter(&self) -> impl Iterator<Item = &(f64, f64)> + '_ {
         let first_iter = self.bucket_min_max.iter().skip(self.last_bucket_index);
         let second_iter = self.bucket_min_max.iter().take(self.last_bucket_index);
         first_iter.chain(second_iter)
@@ -65,9 +65,8 @@ impl SampleBucketer {
         offset_y: u32,
     ) -> Result<()> {
         let (all_min, all_max) = self.all_min_max;
-        let scale_to_window = |x: i32| {
-            let scaled = (i64::from(x) - i64::from(all_min)) as f64
-                / (i64::from(all_max) - i64::from(all_min)) as f64;
+        let scale_to_window = |x: f64| {
+            let scaled = (x - all_min) / (all_max - all_min);
             scaled * f64::from(height) * (1.0 - 2.0 * PADDING_PERC)
                 + f64::from(height) * PADDING_PERC
         };
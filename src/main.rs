@@ -6,29 +6,44 @@ extern crate error_chain;
 extern crate clap;
 
 use composer::core::spec::read::ReadType;
+use composer::core::Time;
 use composer::error::*;
 use composer::pycomposer;
 use composer::DEFAULT_CONFIG_PATH;
+use composer::DEFAULT_SPEC_PATH;
 
 quick_main!(run);
 
 fn run() -> Result<()> {
     // Initialize command line arguments
     let matches = clap_app!(composer =>
-        (@arg spec_path: -s --spec +takes_value +required
-         "Specification of the composition")
-        (@arg config_path: -c --config +takes_value
+        (@arg spec_path: -s --spec +takes_value +global
+         "Specification of the composition, defaults to ./composition.yaml")
+        (@arg config_path: -c --config +takes_value +global
          "Configuration file")
-        (@group read_type +required =>
-         (@arg yaml: -y --yaml "Read spec as yaml file")
-         (@arg python: -p --python "Read spec as python script"))
+        (@group read_type =>
+         (@arg yaml: -y --yaml +global "Read spec as yaml file (default)")
+         (@arg python: -p --python +global "Read spec as python script"))
+        (@subcommand render =>
+         (about: "Render the composition to a .wav file as fast as possible")
+         (@arg duration: -d --duration +takes_value +required
+          "How long to render for, e.g. \"32 bars\"")
+         (@arg out: -o --out +takes_value +required
+          "Path of the .wav file to write to"))
     )
     .get_matches();
-    let spec_path = matches.value_of("spec_path").unwrap();
-    let config_path = matches
-        .value_of("config_path")
-        .unwrap_or(DEFAULT_CONFIG_PATH);
-    let read_type = if matches.is_present("python") {
+    // Global arguments can be given before or after the subcommand
+    let render_matches = matches.subcommand_matches("render");
+    let value_of = |name: &str| {
+        render_matches
+            .and_then(|render_matches| render_matches.value_of(name))
+            .or_else(|| matches.value_of(name))
+    };
+    let spec_path = value_of("spec_path").unwrap_or(DEFAULT_SPEC_PATH);
+    let config_path = value_of("config_path").unwrap_or(DEFAULT_CONFIG_PATH);
+    let read_type = if matches.is_present("python")
+        || render_matches.is_some_and(|render_matches| render_matches.is_present("python"))
+    {
         ReadType::Python
     } else {
         ReadType::Yaml
@@ -43,6 +58,19 @@ fn run() -> Result<()> {
     // TODO: Only do this when reading a python file
     pycomposer::write_library()?;
 
+    if let Some(render_matches) = render_matches {
+        let duration: Time = render_matches.value_of("duration").unwrap().parse()?;
+        let out_path = render_matches.value_of("out").unwrap();
+        composer::core::composer::render_from_file(
+            spec_path.into(),
+            read_type,
+            config_path.into(),
+            duration,
+            out_path.into(),
+        )?;
+        return Ok(());
+    }
+
     composer::core::composer::compose_from_file(spec_path.into(), read_type, config_path.into())?;
     Ok(())
 }